@@ -0,0 +1,249 @@
+// Challonge brackets, for clubs that run their weeklies there.
+//
+// Reads a tournament with its participants and matches from the v1 API,
+//
+//     GET <base>/tournaments/<id>.json?include_participants=1&include_matches=1
+//
+// with the key from CHALLONGE_API_KEY. CHALLONGE_API_URL replaces the base
+// (https://api.challonge.com/v1), e.g. to point at a local stand-in. The same
+// JSON saved to a file can be listed like any imported event (see import.rs).
+//
+// Final ranks become placements and completed matches become sets. Challonge
+// has no IDs shared with start.gg, so players are matched to other events by
+// tag.
+
+use crate::input::{EventEntry, EventTarget};
+use crate::prcalc::{EventData, EventSet, EventStanding, PlayerProfile};
+use crate::query::{QueryError, CLIENT_THREADPOOL};
+use crate::source::TournamentSource;
+use crate::constants::CHALLONGE_API_URL;
+use chrono::{DateTime, FixedOffset};
+use futures::executor::block_on;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize)]
+struct Export {
+    tournament: Tournament
+}
+
+#[derive(Debug, Deserialize)]
+struct Tournament {
+    name: String,
+    started_at: Option<DateTime<FixedOffset>>,
+    start_at: Option<DateTime<FixedOffset>>,
+    #[serde(default)]
+    participants: Vec<ParticipantItem>,
+    #[serde(default)]
+    matches: Vec<MatchItem>
+}
+
+#[derive(Debug, Deserialize)]
+struct ParticipantItem {
+    participant: Participant
+}
+
+#[derive(Debug, Deserialize)]
+struct Participant {
+    id: u64,
+    name: Option<String>,
+    display_name: Option<String>,
+    seed: Option<u32>,
+    final_rank: Option<u32>,
+    /// The IDs this participant plays group stage matches under.
+    #[serde(default)]
+    group_player_ids: Vec<u64>
+}
+
+#[derive(Debug, Deserialize)]
+struct MatchItem {
+    #[serde(rename = "match")]
+    inner: Match
+}
+
+#[derive(Debug, Deserialize)]
+struct Match {
+    state: String,
+    round: i32,
+    player1_id: Option<u64>,
+    winner_id: Option<u64>,
+    loser_id: Option<u64>,
+    scores_csv: Option<String>
+}
+
+/// The Challonge API, or a stand-in for it.
+pub struct Challonge {
+    base_url: String,
+    api_key: Option<String>
+}
+
+impl Challonge {
+    pub fn new(base_url: impl Into<String>, api_key: Option<String>) -> Self {
+        Challonge { base_url: base_url.into(), api_key }
+    }
+
+    pub fn from_env() -> Self {
+        Challonge::new(
+            std::env::var("CHALLONGE_API_URL").unwrap_or_else(|_| CHALLONGE_API_URL.to_string()),
+            std::env::var("CHALLONGE_API_KEY").ok())
+    }
+
+    pub async fn fetch_event(&self, slug: &str) -> Result<EventData, QueryError> {
+        let http_err = |source| QueryError::Http { slug: slug.to_string(), source };
+        let id = slug.trim_start_matches("challonge/");
+        let url = format!("{}/tournaments/{}.json", self.base_url.trim_end_matches('/'), id);
+
+        let mut request = CLIENT_THREADPOOL.get(&url)
+            .query(&[("include_participants", "1"), ("include_matches", "1")]);
+        if let Some(key) = &self.api_key {
+            request = request.query(&[("api_key", key)]);
+        }
+
+        let res = request.send().await.map_err(http_err)?;
+        if res.status() == StatusCode::UNAUTHORIZED || res.status() == StatusCode::FORBIDDEN {
+            let message = match &self.api_key {
+                Some(_) => res.text().await.unwrap_or_else(|_| "unauthorized".to_string()),
+                None => "set CHALLONGE_API_KEY to read Challonge brackets".to_string()
+            };
+            return Err(QueryError::Auth { slug: slug.to_string(), message });
+        }
+        let export: Export = res.error_for_status().map_err(http_err)?
+            .json().await.map_err(http_err)?;
+        to_event(slug, export.tournament).map_err(|what| QueryError::MissingData { slug: slug.to_string(), what })
+    }
+}
+
+impl TournamentSource for Challonge {
+    fn handles(&self, target: &EventTarget) -> bool {
+        matches!(target, EventTarget::Challonge(_))
+    }
+
+    fn load_event(&self, entry: &EventEntry) -> Result<EventData, QueryError> {
+        block_on(self.fetch_event(entry.target.slug()))
+    }
+}
+
+/// Whether a JSON document looks like a saved Challonge tournament.
+pub fn is_export(value: &serde_json::Value) -> bool {
+    value.get("tournament").is_some()
+}
+
+/// Reads a saved Challonge tournament, for imported files.
+pub fn event_from_export(slug: &str, value: serde_json::Value) -> Result<EventData, String> {
+    let export: Export = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    to_event(slug, export.tournament).map_err(|what| format!("there is no {} in this Challonge tournament", what))
+}
+
+fn to_event(slug: &str, tournament: Tournament) -> Result<EventData, &'static str> {
+    if tournament.participants.is_empty() {
+        return Err("participant list");
+    }
+
+    let mut standings = vec![];
+    let mut tags: HashMap<u64, String> = HashMap::new();
+    for item in &tournament.participants {
+        let p = &item.participant;
+        let tag = p.display_name.clone().or_else(|| p.name.clone()).unwrap_or_default();
+        for id in std::iter::once(p.id).chain(p.group_player_ids.iter().copied()) {
+            tags.insert(id, tag.clone());
+        }
+        standings.push(EventStanding {
+            name: tag,
+            player_id: None,
+            placement: p.final_rank.ok_or("final ranking (has the tournament been finalized?)")?,
+            seed: p.seed,
+            team: None,
+            profile: PlayerProfile::default()
+        });
+    }
+
+    let sets = tournament.matches.iter()
+        .map(|item| &item.inner)
+        .filter(|m| m.state == "complete")
+        .filter_map(|m| {
+            let (winner_id, loser_id) = (m.winner_id?, m.loser_id?);
+            let (p1, p2) = parse_scores(m.scores_csv.as_deref().unwrap_or(""));
+            let (winner_score, loser_score) = if Some(winner_id) == m.player1_id { (p1, p2) } else { (p2, p1) };
+            Some(EventSet {
+                round: round_name(m.round),
+                winner: tags.get(&winner_id)?.clone(),
+                winner_id: None,
+                loser: tags.get(&loser_id)?.clone(),
+                loser_id: None,
+                winner_score,
+                loser_score
+            })
+        })
+        .collect();
+
+    Ok(EventData {
+        slug: slug.to_string(),
+        name: tournament.name,
+        num_entrants: tournament.participants.len() as u32,
+        start_date: tournament.started_at.or(tournament.start_at).map(|d| d.date_naive()),
+        videogame: None,
+        standings,
+        sets
+    })
+}
+
+/// `scores_csv` is `player1-player2`, either one set score ("3-1") or one
+/// score per game ("21-15,18-21,21-9"), which is counted into games won.
+/// Scores can be negative ("-1-0" is a DQ). Unreported or unreadable scores
+/// are `None`.
+fn parse_scores(scores_csv: &str) -> (Option<i32>, Option<i32>) {
+    let games: Vec<(i32, i32)> = scores_csv.split(',')
+        .map(str::trim)
+        .filter(|g| !g.is_empty())
+        .map(|g| {
+            let split = g.get(1..)?.find('-')? + 1;
+            Some((g[..split].parse().ok()?, g[split + 1..].parse().ok()?))
+        })
+        .collect::<Option<_>>()
+        .unwrap_or_default();
+
+    match games.as_slice() {
+        [] => (None, None),
+        [(p1, p2)] => (Some(*p1), Some(*p2)),
+        _ => (
+            Some(games.iter().filter(|(a, b)| a > b).count() as i32),
+            Some(games.iter().filter(|(a, b)| b > a).count() as i32)
+        )
+    }
+}
+
+fn round_name(round: i32) -> String {
+    match round {
+        r if r > 0 => format!("Winners Round {}", r),
+        r if r < 0 => format!("Losers Round {}", -r),
+        _ => String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores() {
+        assert_eq!(parse_scores("3-1"), (Some(3), Some(1)));
+        assert_eq!(parse_scores("21-15,18-21,21-9"), (Some(2), Some(1)));
+        assert_eq!(parse_scores(""), (None, None));
+        assert_eq!(parse_scores("3-x"), (None, None));
+    }
+
+    #[test]
+    fn dq_scores_are_negative() {
+        assert_eq!(parse_scores("-1-0"), (Some(-1), Some(0)));
+        assert_eq!(parse_scores("0--1"), (Some(0), Some(-1)));
+        assert_eq!(parse_scores("-1--1"), (Some(-1), Some(-1)));
+    }
+
+    #[test]
+    fn rounds() {
+        assert_eq!(round_name(2), "Winners Round 2");
+        assert_eq!(round_name(-3), "Losers Round 3");
+        assert_eq!(round_name(0), "");
+    }
+}
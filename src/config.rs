@@ -0,0 +1,619 @@
+// Project file (pr.toml) describing one or more seasons.
+//
+//     [[season]]
+//     name = "spring-2026"
+//     start = "2026-01-12"
+//     end = "2026-05-03"
+//     event_file = "spring-2026.txt"
+//     events = ["tournament/foo/event/ultimate-singles weight=2"]
+//
+//     [season.discover]
+//     owners = ["123456"]
+//
+//     [season.scoring]
+//     minimum_entrants = 10
+//     drop_worst = true
+//     exclude_dqs = true
+//     recount_entrants = false
+//     teams = "individuals"
+//     adjust_placements = false
+//     tiers = { C = 0.5, B = 1.0, A = 1.25, S = 1.5 }
+//
+//     [season.scoring.points]
+//     1 = 11.0
+//     2 = 9.5
+//
+//     [season.scoring.decay]
+//     kind = "half_life"
+//     days = 90
+//
+//     [season.scoring.field_strength]
+//     mode = "prior"
+//     ranking = "fall-2025-ranking.txt"
+//
+//     [season.scoring.percentile]
+//     curve = "power"
+//     exponent = 2.0
+//
+//     [season.qualification]
+//     minimum_events = 4
+//
+//     [season.eligibility]
+//     roster = "roster.txt"
+//     locations = ["Troy", "NY"]
+//     affiliations = ["RPI"]
+//
+//     [season.event_settings."tournament/foo/event/ultimate-singles"]
+//     tier = "S"
+//     weight = 1.2
+//
+// A season can rank several games side by side, each from its own events.
+// A game's `scoring` and `qualification` replace the season's for that game,
+// and `tournament/...` lines without `game=` are looked up for every game:
+//
+//     [[season.game]]
+//     name = "Ultimate"
+//     id = 1386
+//
+//     [[season.game]]
+//     name = "Melee"
+//     id = 1
+//
+//     [season.game.qualification]
+//     minimum_events = 3
+
+use crate::constants::*;
+use crate::input::{self, EventEntry};
+use chrono::NaiveDate;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectConfig {
+    #[serde(rename = "season", default)]
+    pub seasons: Vec<SeasonConfig>
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeasonConfig {
+    pub name: String,
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+    /// A tournaments.txt-style file, relative to the project file.
+    pub event_file: Option<PathBuf>,
+    /// Extra event lines, in the same format as tournaments.txt.
+    #[serde(default)]
+    pub events: Vec<String>,
+    pub discover: Option<DiscoveryRules>,
+    #[serde(default)]
+    pub scoring: ScoringConfig,
+    #[serde(default)]
+    pub qualification: QualificationConfig,
+    /// Who may be ranked; everyone, without it.
+    pub eligibility: Option<EligibilityConfig>,
+    /// Tier and weight overrides keyed by event slug, for events that were
+    /// discovered rather than listed.
+    #[serde(default)]
+    pub event_settings: BTreeMap<String, EventSettings>,
+    /// Games ranked separately. Without any, every event goes into one ranking.
+    #[serde(rename = "game", default)]
+    pub games: Vec<GameConfig>
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GameConfig {
+    pub name: String,
+    /// start.gg videogame ID.
+    pub id: u32,
+    pub scoring: Option<ScoringConfig>,
+    pub qualification: Option<QualificationConfig>
+}
+
+/// See eligibility.rs.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EligibilityConfig {
+    /// Players who are always eligible, relative to the project file.
+    pub roster: Option<PathBuf>,
+    /// Cities, states or countries, matched against start.gg profiles.
+    pub locations: Vec<String>,
+    /// Sponsor prefixes, like a school's.
+    pub affiliations: Vec<String>
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventSettings {
+    pub tier: Option<Tier>,
+    pub weight: Option<f64>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Tier {
+    C,
+    B,
+    A,
+    S
+}
+
+impl std::str::FromStr for Tier {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "C" => Ok(Tier::C),
+            "B" => Ok(Tier::B),
+            "A" => Ok(Tier::A),
+            "S" => Ok(Tier::S),
+            _ => Err(format!("tier must be one of C, B, A or S, got \"{}\"", s))
+        }
+    }
+}
+
+impl fmt::Display for Tier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Search criteria for finding events on start.gg, shared by the `discover`
+/// command and a season's `[season.discover]` table.
+#[derive(Debug, Clone, Default, Deserialize, clap::Args)]
+#[serde(deny_unknown_fields)]
+pub struct DiscoveryRules {
+    /// start.gg user ID of a tournament owner/organizer (repeatable)
+    #[arg(long = "owner", value_name = "USER_ID")]
+    #[serde(default)]
+    pub owners: Vec<String>,
+
+    /// Center of a location search, as "lat,lng"
+    #[arg(long, value_name = "LAT,LNG", requires = "radius")]
+    pub near: Option<String>,
+
+    /// Radius of a location search, like "25mi" or "40km"
+    #[arg(long, value_name = "DISTANCE", requires = "near")]
+    pub radius: Option<String>,
+
+    /// League ID or slug whose events should be included (repeatable)
+    #[arg(long = "league", value_name = "ID_OR_SLUG")]
+    #[serde(default)]
+    pub leagues: Vec<String>,
+
+    /// start.gg videogame ID to keep events for
+    #[arg(long, default_value_t = DEFAULT_VIDEOGAME_ID)]
+    #[serde(default = "default_videogame_id")]
+    pub game: u32
+}
+
+fn default_videogame_id() -> u32 {
+    DEFAULT_VIDEOGAME_ID
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScoringConfig {
+    pub points: PointTable,
+    pub minimum_entrants: u32,
+    /// Drop each player's worst result once they've entered more than
+    /// `qualification.minimum_events` events.
+    pub drop_worst: bool,
+    /// Leave out entrants who lost every set they played by DQ.
+    pub exclude_dqs: bool,
+    /// Take those entrants out of the event's entrant count too.
+    pub recount_entrants: bool,
+    pub teams: TeamMode,
+    /// Move eligible players up past ineligible ones who finished ahead of
+    /// them (see eligibility.rs).
+    pub adjust_placements: bool,
+    /// Multiplier for each event tier. Events without a tier count as 1.0.
+    #[serde(deserialize_with = "deserialize_tiers")]
+    pub tiers: BTreeMap<Tier, f64>,
+    pub decay: Option<DecayConfig>,
+    /// Replaces raw entrant counts in the weighted methods with a measure of
+    /// how strong each event's attendees were.
+    pub field_strength: Option<FieldStrengthConfig>,
+    pub percentile: PercentileConfig
+}
+
+// Tiers left out of the config keep their default multiplier.
+fn deserialize_tiers<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BTreeMap<Tier, f64>, D::Error> {
+    let mut tiers = ScoringConfig::default().tiers;
+    tiers.extend(BTreeMap::<Tier, f64>::deserialize(deserializer)?);
+    Ok(tiers)
+}
+
+impl ScoringConfig {
+    fn check(&self) -> Result<(), &'static str> {
+        if let Some(strength) = &self.field_strength {
+            if !(0.0..=1.0).contains(&strength.floor) {
+                return Err("field strength floor must be between 0 and 1");
+            }
+        }
+        if let Some(decay) = &self.decay {
+            if let DecayCurve::HalfLife { days } | DecayCurve::Linear { days } = decay.curve {
+                if days <= 0.0 {
+                    return Err("decay days must be positive");
+                }
+            }
+        }
+        if self.percentile.points.iter().any(|(p, f)| !(0.0..=1.0).contains(p) || !f.is_finite()) {
+            return Err("percentile points must be [percentile, fraction] pairs with the percentile between 0 and 1");
+        }
+        Ok(())
+    }
+
+    pub fn tier_multiplier(&self, tier: Option<Tier>) -> f64 {
+        tier.and_then(|t| self.tiers.get(&t).copied()).unwrap_or(1.0)
+    }
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        ScoringConfig {
+            points: PointTable::default(),
+            minimum_entrants: MINIMUM_ENTRANT_COUNT,
+            drop_worst: true,
+            exclude_dqs: true,
+            recount_entrants: false,
+            teams: TeamMode::Individuals,
+            adjust_placements: false,
+            tiers: BTreeMap::from([
+                (Tier::C, TIER_C_MULTIPLIER),
+                (Tier::B, TIER_B_MULTIPLIER),
+                (Tier::A, TIER_A_MULTIPLIER),
+                (Tier::S, TIER_S_MULTIPLIER)
+            ]),
+            decay: None,
+            field_strength: None,
+            percentile: PercentileConfig::default()
+        }
+    }
+}
+
+/// How doubles and other team entrants are ranked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum TeamMode {
+    /// Everyone on a team gets the team's placement.
+    Individuals,
+    /// Each pairing is ranked as one entry, named after its members.
+    Units
+}
+
+/// Scales each event's points down by how long before `reference` it
+/// happened.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DecayConfig {
+    #[serde(flatten)]
+    pub curve: DecayCurve,
+    /// Defaults to the season's end date, or the most recent event.
+    pub reference: Option<NaiveDate>
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DecayCurve {
+    /// Weight halves every `days` days.
+    HalfLife { days: f64 },
+    /// Weight falls from 1 to 0 over `days` days.
+    Linear { days: f64 },
+    /// Weight is that of the last step whose `after_days` the event is older than.
+    Step { steps: Vec<DecayStep> }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DecayStep {
+    pub after_days: u32,
+    pub weight: f64
+}
+
+impl DecayCurve {
+    pub fn factor(&self, age_days: f64) -> f64 {
+        let age_days = age_days.max(0.0);
+        match self {
+            DecayCurve::HalfLife { days } => 0.5_f64.powf(age_days / days),
+            DecayCurve::Linear { days } => (1.0 - age_days / days).max(0.0),
+            DecayCurve::Step { steps } => steps.iter()
+                .filter(|s| s.after_days as f64 <= age_days)
+                .max_by_key(|s| s.after_days)
+                .map(|s| s.weight)
+                .unwrap_or(1.0)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FieldStrengthConfig {
+    #[serde(flatten)]
+    pub mode: FieldStrengthMode,
+    /// Strength of players who are unranked or scored nothing.
+    #[serde(default = "default_field_strength_floor")]
+    pub floor: f64
+}
+
+fn default_field_strength_floor() -> f64 {
+    FIELD_STRENGTH_FLOOR
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum FieldStrengthMode {
+    /// A previous season's ranking, one tag per line from the top, relative
+    /// to the project file.
+    Prior { ranking: PathBuf },
+    /// A fixed point over this season's own weighted points ranking.
+    Iterative
+}
+
+/// How the percentile method turns the fraction of the field a player beat
+/// into points.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PercentileConfig {
+    pub curve: PercentileCurve,
+    /// For the power curve: exponents above 1 favor top finishes.
+    pub exponent: f64,
+    /// For the piecewise curve: `[percentile, fraction of max_points]` pairs,
+    /// joined by straight lines. `ProjectConfig::load` sorts them.
+    pub points: Vec<(f64, f64)>,
+    /// Points for winning an event.
+    pub max_points: f64
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PercentileCurve {
+    Linear,
+    Power,
+    Piecewise
+}
+
+impl Default for PercentileConfig {
+    fn default() -> Self {
+        PercentileConfig {
+            curve: PercentileCurve::Linear,
+            exponent: 2.0,
+            points: vec![],
+            max_points: FIRST_POINTS
+        }
+    }
+}
+
+impl PercentileConfig {
+    pub fn points(&self, percentile: f64) -> f64 {
+        let percentile = percentile.clamp(0.0, 1.0);
+        let fraction = match self.curve {
+            PercentileCurve::Linear => percentile,
+            PercentileCurve::Power => percentile.powf(self.exponent),
+            PercentileCurve::Piecewise => {
+                let points = &self.points;
+                let below = points.iter().rfind(|p| p.0 <= percentile);
+                let above = points.iter().find(|p| p.0 >= percentile);
+                match (below, above) {
+                    (Some(b), Some(a)) if a.0 > b.0 =>
+                        b.1 + (a.1 - b.1) * (percentile - b.0) / (a.0 - b.0),
+                    (Some(b), _) => b.1,
+                    (None, Some(a)) => a.1,
+                    (None, None) => percentile
+                }
+            }
+        };
+        self.max_points * fraction
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QualificationConfig {
+    pub minimum_events: u32
+}
+
+impl Default for QualificationConfig {
+    fn default() -> Self {
+        QualificationConfig {
+            minimum_events: MINIMUM_EVENTS_TO_QUALIFY
+        }
+    }
+}
+
+/// Points awarded per placement. Placements missing from the table are worth
+/// `ELSE_POINTS`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointTable(BTreeMap<u32, f64>);
+
+impl PointTable {
+    pub fn points(&self, placement: u32) -> f64 {
+        *self.0.get(&placement).unwrap_or(&ELSE_POINTS)
+    }
+
+    /// Every `(placement, points)` in the table, best placement first.
+    pub fn entries(&self) -> Vec<(u32, f64)> {
+        self.0.iter().map(|(p, pts)| (*p, *pts)).collect()
+    }
+
+    pub fn set(&mut self, placement: u32, points: f64) {
+        self.0.insert(placement, points);
+    }
+}
+
+impl Default for PointTable {
+    fn default() -> Self {
+        PointTable([1, 2, 3, 4, 5, 7, 9].into_iter()
+            .map(|placement| (placement, point_values(placement)))
+            .collect())
+    }
+}
+
+// TOML keys are always strings, so `1 = 11.0` arrives as "1".
+impl<'de> Deserialize<'de> for PointTable {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw: BTreeMap<String, f64> = BTreeMap::deserialize(deserializer)?;
+        let mut table = BTreeMap::new();
+        for (key, points) in raw {
+            let placement = key.parse::<u32>().map_err(|_| serde::de::Error::custom(
+                format!("point table keys must be placements, got \"{}\"", key)))?;
+            table.insert(placement, points);
+        }
+        Ok(PointTable(table))
+    }
+}
+
+impl ProjectConfig {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+        let mut project: ProjectConfig = toml::from_str(&text)
+            .map_err(|e| format!("Error in {}: {}", path.display(), e))?;
+
+        let mut names: HashSet<&str> = HashSet::new();
+        for season in &project.seasons {
+            if !names.insert(&season.name) {
+                return Err(format!("Error in {}: season \"{}\" is defined twice", path.display(), season.name));
+            }
+            if let (Some(start), Some(end)) = (season.start, season.end) {
+                if start > end {
+                    return Err(format!("Error in {}: season \"{}\" ends before it starts", path.display(), season.name));
+                }
+            }
+            let game_scoring = season.games.iter().filter_map(|g| g.scoring.as_ref());
+            for scoring in std::iter::once(&season.scoring).chain(game_scoring) {
+                if let Err(e) = scoring.check() {
+                    return Err(format!("Error in {}: season \"{}\" {}", path.display(), season.name, e));
+                }
+            }
+            if let Some(e) = &season.eligibility {
+                if e.roster.is_none() && e.locations.is_empty() && e.affiliations.is_empty() {
+                    return Err(format!("Error in {}: season \"{}\" eligibility needs a roster, locations or affiliations", path.display(), season.name));
+                }
+            }
+            let mut ids: HashSet<u32> = HashSet::new();
+            for game in &season.games {
+                if !ids.insert(game.id) {
+                    return Err(format!("Error in {}: season \"{}\" lists game {} twice", path.display(), season.name, game.id));
+                }
+            }
+        }
+
+        // the piecewise curve is read in percentile order
+        for season in &mut project.seasons {
+            let game_scoring = season.games.iter_mut().filter_map(|g| g.scoring.as_mut());
+            for scoring in std::iter::once(&mut season.scoring).chain(game_scoring) {
+                scoring.percentile.points.sort_by(|a, b| a.0.total_cmp(&b.0));
+            }
+        }
+        Ok(project)
+    }
+
+    pub fn season(&self, name: &str) -> Option<&SeasonConfig> {
+        self.seasons.iter().find(|s| s.name == name)
+    }
+}
+
+impl SeasonConfig {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start.is_none_or(|s| s <= date) && self.end.is_none_or(|e| date <= e)
+    }
+
+    /// The scoring and qualification rules `game` is ranked under, or the
+    /// season's own without one.
+    pub fn rules(&self, game: Option<&GameConfig>) -> (ScoringConfig, QualificationConfig) {
+        (
+            game.and_then(|g| g.scoring.clone()).unwrap_or_else(|| self.scoring.clone()),
+            game.and_then(|g| g.qualification.clone()).unwrap_or_else(|| self.qualification.clone())
+        )
+    }
+
+    /// Collects the season's explicitly listed events, from `event_file`
+    /// (resolved against `base_dir`) and then the inline `events` list.
+    /// Imported standings files are also resolved against `base_dir`.
+    pub fn event_entries(&self, base_dir: &Path) -> Result<Vec<EventEntry>, Vec<String>> {
+        let mut entries = vec![];
+        let mut errors = vec![];
+
+        if let Some(event_file) = &self.event_file {
+            let path = base_dir.join(event_file);
+            let lines: io::Result<Vec<String>> = std::fs::File::open(&path)
+                .and_then(|f| io::BufReader::new(f).lines().collect());
+            match lines {
+                Ok(lines) => match input::parse_event_list(&lines) {
+                    Ok(e) => entries.extend(e),
+                    Err(errs) => errors.extend(errs.iter().map(|e| format!("{} {}", path.display(), e)))
+                },
+                Err(e) => errors.push(format!("Error reading {}: {}", path.display(), e))
+            }
+        }
+
+        for (idx, line) in self.events.iter().enumerate() {
+            match input::parse_line(idx + 1, line) {
+                Ok(Some(entry)) => entries.push(entry),
+                Ok(None) => {},
+                Err(e) => errors.push(format!("season \"{}\" events entry {}: {}", self.name, e.line, e.message))
+            }
+        }
+
+        for entry in &mut entries {
+            if let input::EventTarget::File { path, .. } = &mut entry.target {
+                *path = base_dir.join(&*path);
+            }
+        }
+
+        // a tournament without game= is looked up once for every game
+        if !self.games.is_empty() {
+            entries = entries.into_iter().flat_map(|entry| match entry.target {
+                input::EventTarget::Tournament { ref slug, .. } if entry.game.is_none() => self.games.iter()
+                    .map(|g| EventEntry {
+                        target: input::EventTarget::Tournament { slug: slug.clone(), videogame_id: g.id },
+                        ..entry.clone()
+                    })
+                    .collect(),
+                _ => vec![entry]
+            }).collect();
+        }
+
+        if errors.is_empty() { Ok(entries) } else { Err(errors) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiers_left_out_keep_their_default() {
+        let scoring: ScoringConfig = toml::from_str("tiers = { S = 2.0 }").unwrap();
+        assert_eq!(scoring.tier_multiplier(Some(Tier::S)), 2.0);
+        assert_eq!(scoring.tier_multiplier(Some(Tier::A)), TIER_A_MULTIPLIER);
+        assert_eq!(scoring.tier_multiplier(None), 1.0);
+    }
+
+    #[test]
+    fn decay_curves() {
+        let half_life = DecayCurve::HalfLife { days: 30.0 };
+        assert_eq!(half_life.factor(0.0), 1.0);
+        assert_eq!(half_life.factor(30.0), 0.5);
+        assert_eq!(half_life.factor(90.0), 0.125);
+        // events after the reference date aren't boosted
+        assert_eq!(half_life.factor(-10.0), 1.0);
+
+        let linear = DecayCurve::Linear { days: 100.0 };
+        assert_eq!(linear.factor(25.0), 0.75);
+        assert_eq!(linear.factor(100.0), 0.0);
+        assert_eq!(linear.factor(150.0), 0.0);
+
+        let step = DecayCurve::Step { steps: vec![
+            DecayStep { after_days: 90, weight: 0.5 },
+            DecayStep { after_days: 30, weight: 0.8 }
+        ] };
+        assert_eq!(step.factor(29.0), 1.0);
+        assert_eq!(step.factor(30.0), 0.8);
+        assert_eq!(step.factor(89.5), 0.8);
+        assert_eq!(step.factor(200.0), 0.5);
+    }
+}
@@ -10,14 +10,22 @@ pub const NINTH_POINTS: f64 = 1.0;
 pub const ELSE_POINTS: f64 = 0.0;
 
 pub const MINIMUM_ENTRANT_COUNT: u32 = 10;
+pub const MINIMUM_EVENTS_TO_QUALIFY: u32 = 4;
 
+pub const FIELD_STRENGTH_FLOOR: f64 = 0.1;
+pub const FIELD_STRENGTH_MAX_ITERATIONS: u32 = 100;
+pub const FIELD_STRENGTH_TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CalculationMethods {
     AveragePlacement,
     WeightedPoints,
     MedianPoints,
     MeanPoints,
     UnweightedPoints,
-    OverallPRPlacement
+    OverallPRPlacement,
+    PercentilePoints,
+    SeedPerformance
 }
 
 pub const fn point_values(placement: u32) -> f64 {
@@ -32,3 +40,19 @@ pub const fn point_values(placement: u32) -> f64 {
         _ => ELSE_POINTS
     }
 }
+
+pub const TIER_C_MULTIPLIER: f64 = 0.5;
+pub const TIER_B_MULTIPLIER: f64 = 1.0;
+pub const TIER_A_MULTIPLIER: f64 = 1.25;
+pub const TIER_S_MULTIPLIER: f64 = 1.5;
+
+// Super Smash Bros. Ultimate
+pub const DEFAULT_VIDEOGAME_ID: u32 = 1386;
+
+pub const STARTGG_HOSTS: [&str; 2] = ["start.gg", "smash.gg"];
+pub const CHALLONGE_HOST: &str = "challonge.com";
+// the language prefixes challonge.com puts before a bracket, as in challonge.com/fr/abc123
+pub const CHALLONGE_LANGUAGES: [&str; 16] = [
+    "cs", "da", "de", "en", "es", "fi", "fr", "it", "ja", "ko", "nl", "pl", "pt_BR", "ru", "sv", "zh_CN"
+];
+pub const CHALLONGE_API_URL: &str = "https://api.challonge.com/v1";
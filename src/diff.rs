@@ -0,0 +1,173 @@
+// `diff <old> [new]`: how the rankings moved between two `--format json`
+// results files, e.g. last season's against this one's.
+//
+// For every method both files have, lists each ranked player's rank change
+// (▲ up, ▼ down), their score delta, who is new and who dropped off.
+
+use crate::export::{self, MethodResults, RankingResults};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffFormat {
+    Text,
+    Markdown
+}
+
+#[derive(clap::Args, Debug)]
+pub struct DiffArgs {
+    /// The earlier results, e.g. last season's output.json
+    old: PathBuf,
+
+    /// The later results
+    #[arg(default_value = "output.json")]
+    new: PathBuf,
+
+    /// Only compare this method (e.g. overall, weighted_points)
+    #[arg(long)]
+    method: Option<String>,
+
+    /// Which game's rankings to compare, when the results rank several
+    #[arg(long)]
+    game: Option<String>,
+
+    #[arg(long, value_enum, default_value_t = DiffFormat::Text)]
+    format: DiffFormat,
+
+    /// Write the report here instead of printing it
+    #[arg(short, long)]
+    output: Option<PathBuf>
+}
+
+pub struct RankChange {
+    pub player: String,
+    pub old_rank: Option<u32>,
+    pub new_rank: u32,
+    pub old_score: Option<f64>,
+    pub new_score: f64
+}
+
+impl RankChange {
+    /// Positive when the player moved up.
+    pub fn movement(&self) -> Option<i64> {
+        self.old_rank.map(|old| old as i64 - self.new_rank as i64)
+    }
+
+    fn movement_text(&self) -> String {
+        match self.movement() {
+            None => "NEW".to_string(),
+            Some(0) => "=".to_string(),
+            Some(m) if m > 0 => format!("▲{}", m),
+            Some(m) => format!("▼{}", -m)
+        }
+    }
+
+    fn delta_text(&self) -> String {
+        match self.old_score {
+            Some(old) => format!("{:+.4}", self.new_score - old),
+            None => "-".to_string()
+        }
+    }
+}
+
+pub struct MethodDiff {
+    pub title: String,
+    pub changes: Vec<RankChange>,
+    /// `(player, old rank)` for everyone ranked before but not now.
+    pub dropped: Vec<(String, u32)>
+}
+
+pub fn diff_method(old: &MethodResults, new: &MethodResults) -> MethodDiff {
+    let changes = new.entries.iter().map(|e| {
+        let before = old.entries.iter().find(|o| o.player == e.player);
+        RankChange {
+            player: e.player.clone(),
+            old_rank: before.map(|o| o.rank),
+            new_rank: e.rank,
+            old_score: before.map(|o| o.score),
+            new_score: e.score
+        }
+    }).collect();
+    let dropped = old.entries.iter()
+        .filter(|o| !new.entries.iter().any(|e| e.player == o.player))
+        .map(|o| (o.player.clone(), o.rank))
+        .collect();
+    MethodDiff { title: new.title.clone(), changes, dropped }
+}
+
+pub fn diff_results(old: &RankingResults, new: &RankingResults, method: Option<&str>) -> Vec<MethodDiff> {
+    new.methods.iter()
+        .filter(|m| method.map(|k| k == m.key).unwrap_or(true))
+        .filter_map(|m| old.methods.iter().find(|o| o.key == m.key).map(|o| diff_method(o, m)))
+        .collect()
+}
+
+pub fn diff(args: DiffArgs) -> Result<(), String> {
+    let old = export::load_results(&args.old, args.game.as_deref())?;
+    let new = export::load_results(&args.new, args.game.as_deref())?;
+    let diffs = diff_results(&old, &new, args.method.as_deref());
+    if diffs.is_empty() {
+        return Err(match &args.method {
+            Some(m) => format!("Method \"{}\" isn't in both results files.", m),
+            None => "The results files have no methods in common.".to_string()
+        });
+    }
+
+    let title = format!("{} -> {}", season_name(&old, &args.old), season_name(&new, &args.new));
+    let written = match &args.output {
+        Some(path) => {
+            let mut f = File::create(path).map_err(|e| format!("Error creating {}: {}", path.display(), e))?;
+            write_diff(&mut f, &title, &diffs, args.format)
+        },
+        None => write_diff(&mut io::stdout().lock(), &title, &diffs, args.format)
+    };
+    written.map_err(|e| format!("Error writing the diff: {}", e))
+}
+
+fn season_name(results: &RankingResults, path: &std::path::Path) -> String {
+    results.season.clone().unwrap_or_else(|| path.display().to_string())
+}
+
+fn write_diff(f: &mut dyn Write, title: &str, diffs: &[MethodDiff], format: DiffFormat) -> io::Result<()> {
+    match format {
+        DiffFormat::Text => {
+            writeln!(f, "{}", title)?;
+            writeln!(f, "{}", "=".repeat(title.chars().count()))?;
+            for d in diffs {
+                writeln!(f)?;
+                writeln!(f, "{}", d.title)?;
+                writeln!(f, "{}", "-".repeat(d.title.chars().count()))?;
+                for c in &d.changes {
+                    let old_rank = c.old_rank.map(|r| r.to_string()).unwrap_or_else(|| "-".to_string());
+                    writeln!(f, "{:>3}  {:>5}  {:30}  was {:>3}  score {:>10.4}  ({})",
+                        c.new_rank, c.movement_text(), c.player, old_rank, c.new_score, c.delta_text())?;
+                }
+                for (player, rank) in &d.dropped {
+                    writeln!(f, "  -  {:>5}  {:30}  was {:>3}", "OUT", player, rank)?;
+                }
+            }
+        },
+        DiffFormat::Markdown => {
+            writeln!(f, "# {}", title)?;
+            for d in diffs {
+                writeln!(f)?;
+                writeln!(f, "## {}", d.title)?;
+                writeln!(f)?;
+                writeln!(f, "| Rank | Change | Player | Was | Score | Delta |")?;
+                writeln!(f, "|---:|:---:|---|---:|---:|---:|")?;
+                for c in &d.changes {
+                    let old_rank = c.old_rank.map(|r| r.to_string()).unwrap_or_else(|| "-".to_string());
+                    writeln!(f, "| {} | {} | {} | {} | {:.4} | {} |",
+                        c.new_rank, c.movement_text(), c.player.replace('|', "\\|"), old_rank, c.new_score, c.delta_text())?;
+                }
+                if !d.dropped.is_empty() {
+                    let dropped: Vec<String> = d.dropped.iter().map(|(p, r)| format!("{} (was {})", p, r)).collect();
+                    writeln!(f)?;
+                    writeln!(f, "Dropped off: {}", dropped.join(", "))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
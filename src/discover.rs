@@ -0,0 +1,104 @@
+use crate::config::DiscoveryRules;
+use crate::query::{self, DiscoveredEvent, QueryError, StartGg, TournamentSearch};
+use chrono::{DateTime, NaiveDate};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Write;
+
+#[derive(clap::Args, Debug)]
+pub struct DiscoverArgs {
+    #[command(flatten)]
+    rules: DiscoveryRules,
+
+    /// Only include events starting on or after this date (YYYY-MM-DD)
+    #[arg(long, value_parser = parse_date)]
+    after: Option<NaiveDate>,
+
+    /// Only include events starting on or before this date (YYYY-MM-DD)
+    #[arg(long, value_parser = parse_date)]
+    before: Option<NaiveDate>,
+
+    /// Where to write the candidate event list
+    #[arg(short, long, default_value = "discovered.txt")]
+    output: String
+}
+
+pub fn parse_date(s: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map_err(|e| format!("expected a date like 2026-01-31 ({})", e))
+}
+
+fn format_timestamp(timestamp: Option<query::Timestamp>) -> String {
+    timestamp
+        .and_then(|t| DateTime::from_timestamp(t, 0))
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| "unknown date".to_string())
+}
+
+/// Runs every search in `rules` over the date range, returning each matching
+/// event once, oldest first.
+pub async fn discover_events(rules: &DiscoveryRules, after: Option<NaiveDate>, before: Option<NaiveDate>)
+        -> Result<Vec<DiscoveredEvent>, QueryError> {
+    let mut search = TournamentSearch {
+        owner_id: None,
+        location: None,
+        after_date: after.map(|d| d.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp()),
+        before_date: before.map(|d| d.and_hms_opt(23, 59, 59).unwrap().and_utc().timestamp()),
+        videogame_id: rules.game
+    };
+
+    let startgg = StartGg::from_env();
+    let mut found: Vec<DiscoveredEvent> = vec![];
+
+    for owner in &rules.owners {
+        search.owner_id = Some(owner.clone());
+        let description = format!("owner {}", owner);
+        found.extend(startgg.discover_tournament_events(&search, &description).await?);
+    }
+    search.owner_id = None;
+
+    // owners' tournaments count wherever they are, so only this search is by location
+    if let Some((near, radius)) = rules.near.clone().zip(rules.radius.clone()) {
+        let description = format!("tournaments near {}", near);
+        search.location = Some((near, radius));
+        found.extend(startgg.discover_tournament_events(&search, &description).await?);
+        search.location = None;
+    }
+
+    for league in &rules.leagues {
+        found.extend(startgg.discover_league_events(league, &search).await?);
+    }
+
+    let mut seen: HashSet<String> = HashSet::new();
+    found.retain(|e| seen.insert(e.slug.clone()));
+    found.sort_by_key(|e| e.start_at);
+    Ok(found)
+}
+
+pub async fn discover(args: DiscoverArgs) -> Result<usize, String> {
+    let rules = &args.rules;
+    if rules.owners.is_empty() && rules.near.is_none() && rules.leagues.is_empty() {
+        return Err("Give at least one of --owner, --near/--radius or --league to search by.".to_string());
+    }
+
+    let found = discover_events(rules, args.after, args.before).await.map_err(|e| e.to_string())?;
+
+    let mut outfile = File::create(&args.output)
+        .map_err(|e| format!("Error creating {}: {}", args.output, e))?;
+    write_candidates(&mut outfile, &found)
+        .map_err(|e| format!("Error writing {}: {}", args.output, e))?;
+
+    Ok(found.len())
+}
+
+fn write_candidates(f: &mut dyn Write, events: &[DiscoveredEvent]) -> std::io::Result<()> {
+    writeln!(f, "# Candidate events found by `discover`.")?;
+    writeln!(f, "# Review these, then copy the ones that count into tournaments.txt.")?;
+    writeln!(f)?;
+    for e in events {
+        let entrants = e.num_entrants.map(|n| n.to_string()).unwrap_or_else(|| "?".to_string());
+        writeln!(f, "{:60}  # {} / {}, {}, {} entrants",
+            e.slug, e.tournament_name, e.event_name, format_timestamp(e.start_at), entrants)?;
+    }
+    Ok(())
+}
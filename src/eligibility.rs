@@ -0,0 +1,134 @@
+// Who may be ranked, for PRs limited to a school or region.
+//
+// A player is eligible if they're on the roster, or if start.gg says they're
+// from one of the season's locations or play under one of its affiliations.
+// The roster is a text file with one player per line:
+//
+//     # members and locals, by tag (case doesn't matter)
+//     Alpha
+//     Bravo
+//     # or by start.gg player ID, which survives tag changes
+//     id:1001
+//
+// Ineligible players keep their placements at every event, so the players
+// they beat aren't moved up; they're just left out of the rankings. With
+// `adjust_placements`, eligible players' placements are instead recounted
+// among eligible entrants, bucket by bucket: a local who lost only to a
+// visitor counts as 1st.
+
+use crate::config::EligibilityConfig;
+use crate::prcalc::EventStanding;
+use std::collections::HashSet;
+use std::path::Path;
+
+#[derive(Debug, Clone, Default)]
+pub struct Eligibility {
+    tags: HashSet<String>,
+    player_ids: HashSet<String>,
+    locations: Vec<String>,
+    affiliations: Vec<String>
+}
+
+impl Eligibility {
+    /// Reads the roster `config` names, relative to `base_dir`.
+    pub fn load(config: &EligibilityConfig, base_dir: &Path) -> Result<Self, String> {
+        let mut eligibility = Eligibility {
+            locations: config.locations.clone(),
+            affiliations: config.affiliations.clone(),
+            ..Eligibility::default()
+        };
+
+        if let Some(roster) = &config.roster {
+            let path = base_dir.join(roster);
+            let text = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Error reading roster {}: {}", path.display(), e))?;
+            for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+                match line.strip_prefix("id:") {
+                    Some(id) => eligibility.player_ids.insert(id.trim().to_string()),
+                    None => eligibility.tags.insert(line.to_lowercase())
+                };
+            }
+        }
+        Ok(eligibility)
+    }
+
+    pub fn allows(&self, standing: &EventStanding) -> bool {
+        let profile = &standing.profile;
+        let matches = |list: &[String], value: &Option<String>|
+            value.as_ref().is_some_and(|v| list.iter().any(|l| l.eq_ignore_ascii_case(v)));
+
+        self.tags.contains(&standing.name.to_lowercase())
+            || standing.player_id.as_ref().is_some_and(|id| self.player_ids.contains(id))
+            || matches(&self.locations, &profile.city)
+            || matches(&self.locations, &profile.state)
+            || matches(&self.locations, &profile.country)
+            || matches(&self.affiliations, &profile.prefix)
+    }
+
+    /// A sentence or two on who is ranked, for the methodology section.
+    pub fn describe(&self, adjust_placements: bool) -> String {
+        let mut who = vec![];
+        if !self.tags.is_empty() || !self.player_ids.is_empty() {
+            who.push("on the roster".to_string());
+        }
+        if !self.locations.is_empty() {
+            who.push(format!("from {}", self.locations.join(", ")));
+        }
+        if !self.affiliations.is_empty() {
+            who.push(format!("playing under {}", self.affiliations.join(", ")));
+        }
+        let others = if adjust_placements {
+            "Their placements are recounted among themselves, so losing only to ineligible players counts as 1st."
+        } else {
+            "Everyone else keeps their placements at the events they entered."
+        };
+        format!("Only players {} are ranked. {}", who.join(" or "), others)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prcalc::PlayerProfile;
+    use std::path::PathBuf;
+
+    fn standing(name: &str, player_id: Option<&str>, profile: PlayerProfile) -> EventStanding {
+        EventStanding {
+            name: name.to_string(),
+            player_id: player_id.map(str::to_string),
+            placement: 1,
+            seed: None,
+            team: None,
+            profile
+        }
+    }
+
+    #[test]
+    fn roster_locations_and_affiliations() {
+        let dir = std::env::temp_dir().join(format!("rpipr-roster-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("roster.txt"), "# members\n  Alpha \n\nid:1001\n").unwrap();
+        let config = EligibilityConfig {
+            roster: Some(PathBuf::from("roster.txt")),
+            locations: vec!["Troy".to_string(), "VT".to_string()],
+            affiliations: vec!["RPI".to_string()]
+        };
+        let eligibility = Eligibility::load(&config, &dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let from = |city: &str, state: &str| PlayerProfile {
+            city: Some(city.to_string()),
+            state: Some(state.to_string()),
+            ..PlayerProfile::default()
+        };
+        let rpi = PlayerProfile { prefix: Some("rpi".to_string()), ..PlayerProfile::default() };
+        assert!(eligibility.allows(&standing("ALPHA", None, PlayerProfile::default())));
+        assert!(eligibility.allows(&standing("New Tag", Some("1001"), PlayerProfile::default())));
+        assert!(eligibility.allows(&standing("Charlie", None, from("troy", "NY"))));
+        assert!(eligibility.allows(&standing("Delta", None, from("Burlington", "VT"))));
+        assert!(eligibility.allows(&standing("Echo", None, rpi)));
+        assert!(!eligibility.allows(&standing("Foxtrot", Some("2002"), from("Albany", "NY"))));
+        // the comment line isn't a tag
+        assert!(!eligibility.allows(&standing("# members", None, PlayerProfile::default())));
+    }
+}
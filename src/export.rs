@@ -0,0 +1,482 @@
+// Ranking results and the writers for `--format text|json|csv`. Markdown and
+// HTML reports live in report.rs.
+//
+// JSON holds the whole run in one document:
+//
+//     { "season": "spring-2026", "game": null,
+//       "events": [ { "id": 0, "slug": "tournament/foo/event/singles", ... } ],
+//       "methods": [ { "key": "weighted_points", "title": "...", "entries": [
+//           { "rank": 1, "player": "...", "score": 7.5, "events_entered": 4,
+//             "events": [ { "event_id": 0, "placement": 1, "points": 10.0, ... } ] } ] } ] }
+//
+// A season that ranks several games (see config.rs) writes an array of these,
+// one per game, named by "game".
+//
+// CSV is long format, one row per method, player and event entered, so it can
+// be pivoted in a spreadsheet, with a leading game column when games are
+// ranked separately. Text is the fixed-width output.txt layout and only shows
+// the top of each method.
+
+use crate::config::{DecayCurve, FieldStrengthConfig, FieldStrengthMode, PercentileConfig, PercentileCurve, TeamMode, Tier};
+use crate::constants::{CalculationMethods, ELSE_POINTS};
+use crate::prcalc::{PROrder, Player, PowerRankings};
+use crate::report;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+    Markdown,
+    Html
+}
+
+impl OutputFormat {
+    pub fn default_output(&self) -> &'static str {
+        match self {
+            OutputFormat::Text => "output.txt",
+            OutputFormat::Json => "output.json",
+            OutputFormat::Csv => "output.csv",
+            OutputFormat::Markdown => "output.md",
+            OutputFormat::Html => "output.html"
+        }
+    }
+
+    pub fn default_template(&self) -> Option<&'static str> {
+        match self {
+            OutputFormat::Markdown => Some(report::DEFAULT_MARKDOWN_TEMPLATE),
+            OutputFormat::Html => Some(report::DEFAULT_HTML_TEMPLATE),
+            _ => None
+        }
+    }
+}
+
+/// One ranking method, in the order the methods are run. The overall PR
+/// averages the ranks of every method before it.
+pub struct MethodSpec {
+    pub method: CalculationMethods,
+    pub key: &'static str,
+    pub title: &'static str,
+    /// Column heading where methods sit side by side.
+    pub short: &'static str,
+    pub progress_label: &'static str,
+    pub order: PROrder,
+    /// How many players the text output lists.
+    pub shown: usize
+}
+
+pub const METHODS: [MethodSpec; 8] = [
+    MethodSpec {
+        method: CalculationMethods::AveragePlacement, key: "average_placement", short: "Avg",
+        title: "Average Placement Statistics", progress_label: "Average Placement",
+        order: PROrder::LoFirst, shown: 12
+    },
+    MethodSpec {
+        method: CalculationMethods::WeightedPoints, key: "weighted_points", short: "Weighted",
+        title: "Weighted Points (based on minimum # entrants)", progress_label: "Weighted Points",
+        order: PROrder::HiFirst, shown: 12
+    },
+    MethodSpec {
+        method: CalculationMethods::MedianPoints, key: "median_points", short: "Median",
+        title: "Median Points Statistics", progress_label: "Median Points",
+        order: PROrder::HiFirst, shown: 12
+    },
+    MethodSpec {
+        method: CalculationMethods::MeanPoints, key: "mean_points", short: "Mean",
+        title: "Mean Points Statistics", progress_label: "Mean Points",
+        order: PROrder::HiFirst, shown: 12
+    },
+    MethodSpec {
+        method: CalculationMethods::UnweightedPoints, key: "unweighted_points", short: "Unweighted",
+        title: "Unweighted Points Statistics", progress_label: "Unweighted Points",
+        order: PROrder::HiFirst, shown: 12
+    },
+    MethodSpec {
+        method: CalculationMethods::OverallPRPlacement, key: "overall", short: "Overall",
+        title: "Overall PR Ordering", progress_label: "PR Placement",
+        order: PROrder::LoFirst, shown: 20
+    },
+    // after the overall ordering, so these don't change the overall PR
+    MethodSpec {
+        method: CalculationMethods::PercentilePoints, key: "percentile_points", short: "Percentile",
+        title: "Percentile Points Statistics", progress_label: "Percentile Points",
+        order: PROrder::HiFirst, shown: 12
+    },
+    MethodSpec {
+        method: CalculationMethods::SeedPerformance, key: "seed_performance", short: "SPR",
+        title: "Seed Performance (placement buckets above/below seed)", progress_label: "Seed Performance",
+        order: PROrder::HiFirst, shown: 12
+    }
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankingResults {
+    pub season: Option<String>,
+    /// The game this ranking is for, when a season ranks several.
+    pub game: Option<String>,
+    pub methodology: Methodology,
+    pub events: Vec<EventSummary>,
+    /// Players who entered events but can't be ranked (see eligibility.rs).
+    pub ineligible: Vec<String>,
+    pub methods: Vec<MethodResults>
+}
+
+/// The scoring rules a run used, for reports to explain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Methodology {
+    pub minimum_events: u32,
+    pub minimum_entrants: u32,
+    pub drop_worst: bool,
+    /// `(placement, points)`; placements not listed are worth `other_points`.
+    pub points: Vec<(u32, f64)>,
+    pub other_points: f64,
+    pub tiers: BTreeMap<Tier, f64>,
+    pub decay: Option<String>,
+    pub field_strength: Option<String>,
+    pub percentile: String,
+    pub dqs: Option<String>,
+    pub teams: Option<String>,
+    pub eligibility: Option<String>
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventSummary {
+    pub id: u32,
+    pub slug: String,
+    pub name: String,
+    pub date: Option<NaiveDate>,
+    pub entrants: u32,
+    /// Entrant count after field strength adjustment.
+    pub strength: f64,
+    pub tier: Option<Tier>,
+    pub tier_multiplier: f64,
+    pub weight: f64,
+    pub decay: f64,
+    /// Everything above multiplied together.
+    pub multiplier: f64,
+    /// Entrants left out for being DQ'd out of every set.
+    pub excluded: Vec<String>
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MethodResults {
+    pub key: String,
+    pub title: String,
+    pub entries: Vec<RankEntry>
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankEntry {
+    pub rank: u32,
+    pub player: String,
+    pub score: f64,
+    pub events_entered: u32,
+    pub seeded_events: u32,
+    pub seed_performance: Option<f64>,
+    /// The event whose result the drop-worst rule threw out.
+    pub dropped_event: Option<u32>,
+    pub events: Vec<PlayerEvent>
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerEvent {
+    pub event_id: u32,
+    pub event: String,
+    pub placement: u32,
+    /// The bracket's placement, when `placement` was adjusted for ineligible
+    /// players ahead.
+    pub raw_placement: Option<u32>,
+    pub seed: Option<u32>,
+    /// What this result was worth under the method; `None` for methods that
+    /// don't score events one at a time.
+    pub points: Option<f64>
+}
+
+impl RankingResults {
+    pub fn new(season: Option<String>, game: Option<String>, pr: &PowerRankings) -> Self {
+        let events = pr.tournaments().iter().enumerate().map(|(id, t)| EventSummary {
+            id: id as u32,
+            slug: t.slug.clone(),
+            name: t.tournament_name.clone(),
+            date: t.start_date,
+            entrants: t.tournament_entrants,
+            strength: pr.event_size(t),
+            tier: t.tier,
+            tier_multiplier: pr.scoring().tier_multiplier(t.tier),
+            weight: t.weight,
+            decay: pr.decay_factor(t),
+            multiplier: pr.tournament_multiplier(t),
+            excluded: t.excluded.clone()
+        }).collect();
+        RankingResults {
+            season,
+            game,
+            methodology: Methodology::new(pr),
+            events,
+            ineligible: pr.ineligible_players(),
+            methods: vec![]
+        }
+    }
+
+    /// Records one method's ranking, as returned by `PowerRankings::rank_players`.
+    pub fn add_method(&mut self, spec: &MethodSpec, pr: &PowerRankings, ranked: &[Player]) {
+        // unseeded players sort last in seed performance and are left out of it
+        let ranked = ranked.iter().filter(|p| spec.method != CalculationMethods::SeedPerformance
+            || p.average_seed_performance().is_some());
+        let entries = (1..).zip(ranked).map(|(rank, p)| RankEntry {
+            rank,
+            player: p.get_name().to_string(),
+            score: p.get_score(),
+            events_entered: p.get_num_tournaments_entered(),
+            seeded_events: p.get_num_seeded_tournaments(),
+            seed_performance: p.average_seed_performance(),
+            dropped_event: pr.dropped_event(spec.method, p),
+            events: p.get_placements().into_iter().map(|(event_id, placement)| PlayerEvent {
+                event_id,
+                event: self.event_name(event_id).to_string(),
+                placement,
+                raw_placement: p.get_raw_placement(event_id).filter(|raw| *raw != placement),
+                seed: p.get_seed(event_id),
+                points: pr.event_points(spec.method, event_id, placement)
+            }).collect()
+        }).collect();
+        self.methods.push(MethodResults {
+            key: spec.key.to_string(),
+            title: spec.title.to_string(),
+            entries
+        });
+    }
+
+    pub fn event_name(&self, event_id: u32) -> &str {
+        self.events.get(event_id as usize).map(|e| e.name.as_str()).unwrap_or("?")
+    }
+}
+
+impl Methodology {
+    pub fn new(pr: &PowerRankings) -> Self {
+        let scoring = pr.scoring();
+        Methodology {
+            minimum_events: pr.qualification().minimum_events,
+            minimum_entrants: scoring.minimum_entrants,
+            drop_worst: scoring.drop_worst,
+            points: scoring.points.entries(),
+            other_points: ELSE_POINTS,
+            tiers: scoring.tiers.clone(),
+            decay: scoring.decay.as_ref().map(|d| describe_decay(&d.curve, pr.decay_reference_date())),
+            field_strength: scoring.field_strength.as_ref().map(describe_field_strength),
+            percentile: describe_percentile(&scoring.percentile),
+            dqs: scoring.exclude_dqs.then(|| if scoring.recount_entrants {
+                "Entrants DQ'd out of every set they played are left out of the standings \
+                    and the entrant count.".to_string()
+            } else {
+                "Entrants DQ'd out of every set they played are left out of the standings.".to_string()
+            }),
+            teams: (scoring.teams == TeamMode::Units)
+                .then(|| "Doubles teams are ranked as units, one entry per pairing.".to_string()),
+            eligibility: pr.eligibility().map(|e| e.describe(scoring.adjust_placements))
+        }
+    }
+}
+
+fn describe_decay(curve: &DecayCurve, reference: Option<NaiveDate>) -> String {
+    let reference = reference.map(|d| d.to_string()).unwrap_or_else(|| "the latest event".to_string());
+    match curve {
+        DecayCurve::HalfLife { days } =>
+            format!("Points halve for every {} days an event is older than {}.", days, reference),
+        DecayCurve::Linear { days } =>
+            format!("Points fall from full to zero over the {} days before {}.", days, reference),
+        DecayCurve::Step { steps } => {
+            let mut steps = steps.clone();
+            steps.sort_by_key(|s| s.after_days);
+            let steps: Vec<String> = steps.iter()
+                .map(|s| format!("x{:.2} after {} days", s.weight, s.after_days))
+                .collect();
+            format!("Points are scaled by how long before {} an event was: {}.", reference, steps.join(", "))
+        }
+    }
+}
+
+fn describe_field_strength(strength: &FieldStrengthConfig) -> String {
+    match &strength.mode {
+        FieldStrengthMode::Prior { ranking } => format!(
+            "Event sizes count attendees by their rank in {}; unranked players count as {:.2} of an entrant.",
+            ranking.display(), strength.floor),
+        FieldStrengthMode::Iterative => format!(
+            "Event sizes count attendees by their strength in this ranking, recalculated until it settles; \
+             players without points count as {:.2} of an entrant.", strength.floor)
+    }
+}
+
+fn describe_percentile(percentile: &PercentileConfig) -> String {
+    let curve = match percentile.curve {
+        PercentileCurve::Linear => "times the fraction of the field beaten".to_string(),
+        PercentileCurve::Power => format!("times the fraction of the field beaten to the power {}", percentile.exponent),
+        PercentileCurve::Piecewise => {
+            let points: Vec<String> = percentile.points.iter()
+                .map(|(p, f)| format!("{:.2} -> {:.2}", p, f))
+                .collect();
+            format!("times a fraction read off the curve {}", points.join(", "))
+        }
+    };
+    format!("Percentile points are {:.2} {}.", percentile.max_points, curve)
+}
+
+/// Reads results saved with `--format json`. Files with several games need
+/// `game` to say which one to read.
+pub fn load_results(path: &Path, game: Option<&str>) -> Result<RankingResults, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+    let not_results = |e: serde_json::Error| format!("{} isn't a results file from --format json: {}", path.display(), e);
+    let value: serde_json::Value = serde_json::from_str(&text).map_err(not_results)?;
+    let mut all: Vec<RankingResults> = if value.is_array() {
+        serde_json::from_value(value).map_err(not_results)?
+    } else {
+        vec![serde_json::from_value(value).map_err(not_results)?]
+    };
+
+    let found = pick_game(&all, game).map_err(|e| format!("{} {}", path.display(), e))?;
+    Ok(all.swap_remove(found))
+}
+
+/// Which of `all` is the ranking for `game`, matched without regard to case.
+/// `game` may be left out when there's only one.
+pub fn pick_game(all: &[RankingResults], game: Option<&str>) -> Result<usize, String> {
+    let games: Vec<&str> = all.iter().filter_map(|r| r.game.as_deref()).collect();
+    match game {
+        Some(name) => all.iter().position(|r| r.game.as_deref().is_some_and(|g| g.eq_ignore_ascii_case(name)))
+            .ok_or_else(|| format!("has no rankings for \"{}\" (have: {})", name, games.join(", "))),
+        None if all.len() == 1 => Ok(0),
+        None if all.is_empty() => Err("holds no rankings".to_string()),
+        None => Err(format!("ranks several games, pick one with --game (have: {})", games.join(", ")))
+    }
+}
+
+/// Writes `results`, one per game, in `format`. `template` replaces the
+/// default report template for Markdown and HTML, and is ignored otherwise.
+pub fn write_results(f: &mut dyn Write, results: &[RankingResults], format: OutputFormat,
+        template: Option<&str>) -> io::Result<()> {
+    let template = template.or(format.default_template()).unwrap_or_default();
+    match format {
+        OutputFormat::Text => {
+            for r in results {
+                write_text(f, r)?;
+            }
+            Ok(())
+        },
+        OutputFormat::Json => {
+            match results {
+                [single] => serde_json::to_writer_pretty(&mut *f, single)?,
+                _ => serde_json::to_writer_pretty(&mut *f, results)?
+            }
+            writeln!(f)
+        },
+        OutputFormat::Csv => write_csv(f, results),
+        OutputFormat::Markdown => report::write_markdown(f, results, template),
+        OutputFormat::Html => report::write_html(f, results, template)
+    }
+}
+
+fn write_text(f: &mut dyn Write, results: &RankingResults) -> io::Result<()> {
+    if let Some(game) = &results.game {
+        writeln!(f, "{}", "#".repeat(game.len() + 4))?;
+        writeln!(f, "# {} #", game)?;
+        writeln!(f, "{}", "#".repeat(game.len() + 4))?;
+        writeln!(f)?;
+    }
+    writeln!(f, "Event Weights")?;
+    writeln!(f, "=============")?;
+    writeln!(f, "{:30}  {:10}  {:>8}  {:>8}  {:>4}  {:>6}  {:>6}  {:>6}  {:>6}",
+        "Event", "Date", "Entrants", "Strength", "Tier", "Tier x", "Manual", "Decay", "Final")?;
+    for e in &results.events {
+        let date = e.date.map(|d| d.to_string()).unwrap_or_else(|| "?".to_string());
+        let tier = e.tier.map(|t| t.to_string()).unwrap_or_else(|| "-".to_string());
+        writeln!(f, "{:30}  {:10}  {:>8}  {:>8.2}  {:>4}  {:>6.2}  {:>6.2}  {:>6.2}  {:>6.2}",
+            e.name, date, e.entrants, e.strength, tier, e.tier_multiplier, e.weight, e.decay, e.multiplier)?;
+    }
+    writeln!(f)?;
+    if results.events.iter().any(|e| !e.excluded.is_empty()) {
+        writeln!(f, "Left out for being DQ'd out of every set:")?;
+        for e in results.events.iter().filter(|e| !e.excluded.is_empty()) {
+            writeln!(f, "    {:30}  {}", e.name, e.excluded.join(", "))?;
+        }
+        writeln!(f)?;
+    }
+    if !results.ineligible.is_empty() {
+        writeln!(f, "Not eligible to be ranked (placements kept):")?;
+        writeln!(f, "    {}", results.ineligible.join(", "))?;
+        writeln!(f)?;
+    }
+    writeln!(f)?;
+
+    for method in &results.methods {
+        let shown = METHODS.iter().find(|m| m.key == method.key).map(|m| m.shown).unwrap_or(usize::MAX);
+        writeln!(f, "{}", method.title)?;
+        writeln!(f, "{}", "=".repeat(method.title.len()))?;
+        for e in method.entries.iter().take(shown) {
+            match method.key.as_str() {
+                "overall" => {
+                    let spr = e.seed_performance
+                        .map(|s| format!("{:+.2}", s))
+                        .unwrap_or_else(|| "n/a".to_string());
+                    writeln!(f, "{:>3}  {:30}  AVG = {:5.4}  SPR = {:>5}", e.rank, e.player, e.score, spr)?;
+                },
+                "seed_performance" => {
+                    writeln!(f, "{:>3}  {:30}  SPR = {:+5.2}  ({} seeded events)",
+                        e.rank, e.player, e.score, e.seeded_events)?;
+                },
+                _ => writeln!(f, "{:>3}  {:30}  AVG = {:5.4}", e.rank, e.player, e.score)?
+            }
+        }
+        writeln!(f)?;
+        writeln!(f)?;
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct CsvRow<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    game: Option<&'a str>,
+    method: &'a str,
+    rank: u32,
+    player: &'a str,
+    score: f64,
+    events_entered: u32,
+    event: &'a str,
+    date: Option<NaiveDate>,
+    placement: u32,
+    raw_placement: Option<u32>,
+    seed: Option<u32>,
+    points: Option<f64>
+}
+
+fn write_csv(f: &mut dyn Write, all: &[RankingResults]) -> io::Result<()> {
+    let mut writer = csv::Writer::from_writer(f);
+    for (results, method) in all.iter().flat_map(|r| r.methods.iter().map(move |m| (r, m))) {
+        for entry in &method.entries {
+            for event in &entry.events {
+                writer.serialize(CsvRow {
+                    game: results.game.as_deref(),
+                    method: &method.key,
+                    rank: entry.rank,
+                    player: &entry.player,
+                    score: entry.score,
+                    events_entered: entry.events_entered,
+                    event: results.events.get(event.event_id as usize).map(|e| e.slug.as_str()).unwrap_or("?"),
+                    date: results.events.get(event.event_id as usize).and_then(|e| e.date),
+                    placement: event.placement,
+                    raw_placement: event.raw_placement,
+                    seed: event.seed,
+                    points: event.points
+                })?;
+            }
+        }
+    }
+    writer.flush()
+}
+
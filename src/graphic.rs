@@ -0,0 +1,297 @@
+// `export-graphic`: the overall PR's top N as an SVG card, rasterized to PNG.
+//
+// Works from a `--format json` results file, so it needs no network. The look
+// comes from a TOML template, with paths relative to the template file:
+//
+//     title = "Spring 2026 Power Rankings"
+//     width = 1080
+//     background = "#101820"
+//     background_image = "bg.png"       # drawn over `background`, stretched to fit
+//     text_color = "#ffffff"
+//     accent_color = "#f2aa4c"
+//     row_color = "#ffffff14"
+//     font_family = "Inter"
+//     font_files = ["fonts/Inter-Bold.ttf"]
+//     assets = "assets"                 # portraits, looked up as <assets>/<tag>.png, with
+//                                       # characters file names can't have, like /, as _
+//
+//     [portraits]                       # tags whose image isn't <tag>.png, inside assets
+//     "Some Player" = "mains/fox.png"
+
+use crate::export::{self, RankingResults};
+use base64::Engine;
+use resvg::usvg::fontdb;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+const HEADER_HEIGHT: u32 = 170;
+const ROW_HEIGHT: u32 = 84;
+const ROW_GAP: u32 = 10;
+const MARGIN: u32 = 40;
+const PORTRAIT_SIZE: u32 = 68;
+/// Narrowest card that still fits a rank, a name and an event count.
+const MIN_WIDTH: u32 = 320;
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "webp", "gif"];
+
+#[derive(clap::Args, Debug)]
+pub struct GraphicArgs {
+    /// Results written by `--format json`
+    #[arg(long, default_value = "output.json")]
+    results: PathBuf,
+
+    /// Which game's PR to show, when the results rank several
+    #[arg(long)]
+    game: Option<String>,
+
+    /// How many players to show
+    #[arg(long, default_value_t = 10)]
+    top: usize,
+
+    /// Graphic template (TOML); the built-in look is used without one
+    #[arg(long)]
+    template: Option<PathBuf>,
+
+    /// Where to write the SVG; the PNG goes next to it
+    #[arg(short, long, default_value = "pr.svg")]
+    output: PathBuf
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GraphicTemplate {
+    /// Defaults to the results' season name and game.
+    pub title: Option<String>,
+    pub width: u32,
+    pub background: String,
+    pub background_image: Option<PathBuf>,
+    pub text_color: String,
+    pub accent_color: String,
+    pub row_color: String,
+    pub font_family: String,
+    /// Fonts to load for the PNG on top of the system's, so the card looks
+    /// the same on every machine.
+    pub font_files: Vec<PathBuf>,
+    pub assets: PathBuf,
+    pub portraits: BTreeMap<String, PathBuf>
+}
+
+impl Default for GraphicTemplate {
+    fn default() -> Self {
+        GraphicTemplate {
+            title: None,
+            width: 1080,
+            background: "#101820".to_string(),
+            background_image: None,
+            text_color: "#ffffff".to_string(),
+            accent_color: "#f2aa4c".to_string(),
+            row_color: "#ffffff14".to_string(),
+            font_family: "sans-serif".to_string(),
+            font_files: vec![],
+            assets: PathBuf::from("assets"),
+            portraits: BTreeMap::new()
+        }
+    }
+}
+
+impl GraphicTemplate {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+        let mut template: GraphicTemplate = toml::from_str(&text)
+            .map_err(|e| format!("Error in {}: {}", path.display(), e))?;
+        if template.width < MIN_WIDTH {
+            return Err(format!("Error in {}: width must be at least {}", path.display(), MIN_WIDTH));
+        }
+
+        let base = path.parent().unwrap_or(Path::new("."));
+        template.background_image = template.background_image.map(|p| base.join(p));
+        template.font_files = template.font_files.iter().map(|p| base.join(p)).collect();
+        template.assets = base.join(&template.assets);
+        Ok(template)
+    }
+
+    /// The image to show next to `tag`, if there is one.
+    fn portrait(&self, tag: &str) -> Result<Option<PathBuf>, String> {
+        if let Some(path) = self.portraits.get(tag) {
+            let path = self.assets.join(path);
+            let resolved = path.canonicalize()
+                .map_err(|e| format!("Error reading portrait {}: {}", path.display(), e))?;
+            let assets = self.assets.canonicalize()
+                .map_err(|e| format!("Error reading {}: {}", self.assets.display(), e))?;
+            if !resolved.starts_with(&assets) {
+                return Err(format!("The portrait for \"{}\", {}, is outside {}", tag, path.display(), self.assets.display()));
+            }
+            return Ok(Some(resolved));
+        }
+        // team names like "A / B" mustn't reach outside the assets directory
+        let name: String = tag.chars()
+            .map(|c| if c.is_control() || "/\\:*?\"<>|".contains(c) { '_' } else { c })
+            .collect();
+        let name = name.trim_start_matches('.');
+        if name.is_empty() {
+            return Ok(None);
+        }
+        Ok(IMAGE_EXTENSIONS.iter()
+            .map(|ext| self.assets.join(format!("{}.{}", name, ext)))
+            .find(|p| p.is_file()))
+    }
+}
+
+pub fn export_graphic(args: GraphicArgs) -> Result<(), String> {
+    if args.output.extension().is_some_and(|e| e.eq_ignore_ascii_case("png")) {
+        return Err(format!("{} would be overwritten by the PNG; -o names the SVG, like pr.svg", args.output.display()));
+    }
+    let png = args.output.with_extension("png");
+    let results = export::load_results(&args.results, args.game.as_deref())?;
+    let template = match &args.template {
+        Some(path) => GraphicTemplate::load(path)?,
+        None => GraphicTemplate::default()
+    };
+
+    let svg = render_svg(&results, &template, args.top)?;
+    std::fs::write(&args.output, &svg)
+        .map_err(|e| format!("Error writing {}: {}", args.output.display(), e))?;
+
+    rasterize(&svg, &template, &png)?;
+    println!("Wrote {} and {}.", args.output.display(), png.display());
+    Ok(())
+}
+
+pub fn render_svg(results: &RankingResults, template: &GraphicTemplate, top: usize) -> Result<String, String> {
+    let overall = results.methods.iter().find(|m| m.key == "overall")
+        .ok_or("The results file has no overall PR ordering.")?;
+    let entries: Vec<_> = overall.entries.iter().take(top).collect();
+
+    let width = template.width;
+    let height = HEADER_HEIGHT + entries.len() as u32 * (ROW_HEIGHT + ROW_GAP) + MARGIN;
+    let title = template.title.clone().unwrap_or_else(|| match (&results.season, &results.game) {
+        (Some(season), Some(game)) => format!("{} {} Power Rankings", season, game),
+        (Some(name), None) | (None, Some(name)) => format!("{} Power Rankings", name),
+        (None, None) => "Power Rankings".to_string()
+    });
+
+    let mut svg = String::new();
+    let w = &mut svg;
+    writeln!(w, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#).unwrap();
+    writeln!(w, r#"<rect width="100%" height="100%" fill="{}"/>"#, xml_escape(&template.background)).unwrap();
+    if let Some(path) = &template.background_image {
+        writeln!(w, r#"<image href="{}" width="{width}" height="{height}" preserveAspectRatio="xMidYMid slice"/>"#,
+            data_uri(path)?).unwrap();
+    }
+    writeln!(w, r#"<g font-family="{}" fill="{}">"#,
+        xml_escape(&template.font_family), xml_escape(&template.text_color)).unwrap();
+    writeln!(w, r#"<text x="{}" y="100" font-size="56" font-weight="bold" text-anchor="middle">{}</text>"#,
+        width / 2, xml_escape(&title)).unwrap();
+    writeln!(w, r#"<rect x="{}" y="124" width="{}" height="4" fill="{}"/>"#,
+        MARGIN, width - 2 * MARGIN, xml_escape(&template.accent_color)).unwrap();
+
+    for (idx, entry) in entries.iter().enumerate() {
+        let y = HEADER_HEIGHT + idx as u32 * (ROW_HEIGHT + ROW_GAP);
+        let text_y = y + ROW_HEIGHT / 2 + 13;
+        writeln!(w, r#"<rect x="{}" y="{}" width="{}" height="{}" rx="12" fill="{}"/>"#,
+            MARGIN, y, width - 2 * MARGIN, ROW_HEIGHT, xml_escape(&template.row_color)).unwrap();
+        writeln!(w, r#"<text x="{}" y="{}" font-size="40" font-weight="bold" text-anchor="end" fill="{}">{}</text>"#,
+            MARGIN + 80, text_y, xml_escape(&template.accent_color), entry.rank).unwrap();
+
+        let mut name_x = MARGIN + 110;
+        if let Some(path) = template.portrait(&entry.player)? {
+            writeln!(w, r#"<image href="{}" x="{}" y="{}" width="{size}" height="{size}" preserveAspectRatio="xMidYMid meet"/>"#,
+                data_uri(&path)?, name_x, y + (ROW_HEIGHT - PORTRAIT_SIZE) / 2, size = PORTRAIT_SIZE).unwrap();
+            name_x += PORTRAIT_SIZE + 20;
+        }
+        writeln!(w, r#"<text x="{}" y="{}" font-size="36">{}</text>"#,
+            name_x, text_y - 2, xml_escape(&entry.player)).unwrap();
+        writeln!(w, r#"<text x="{}" y="{}" font-size="24" text-anchor="end" opacity="0.7">{} events</text>"#,
+            width - MARGIN - 30, text_y - 4, entry.events_entered).unwrap();
+    }
+
+    writeln!(w, "</g>").unwrap();
+    writeln!(w, "</svg>").unwrap();
+    Ok(svg)
+}
+
+fn rasterize(svg: &str, template: &GraphicTemplate, png: &Path) -> Result<(), String> {
+    let mut options = resvg::usvg::Options::default();
+    let fonts = options.fontdb_mut();
+    fonts.load_system_fonts();
+    for path in &template.font_files {
+        fonts.load_font_file(path).map_err(|e| format!("Error loading font {}: {}", path.display(), e))?;
+    }
+
+    // the generic families default to Windows fonts, so point them at an
+    // installed one instead of drawing no text at all
+    let sans_serif = fontdb::Query { families: &[fontdb::Family::SansSerif], ..Default::default() };
+    if fonts.query(&sans_serif).is_none() {
+        let families: Vec<String> = fonts.faces().filter_map(|f| f.families.first().map(|(name, _)| name.clone())).collect();
+        let fallback = families.iter().find(|f| f.contains("Sans") && !f.contains("Mono")).or(families.first()).cloned();
+        if let Some(family) = fallback {
+            fonts.set_sans_serif_family(family.clone());
+            options.font_family = family;
+        }
+    }
+
+    let tree = resvg::usvg::Tree::from_str(svg, &options).map_err(|e| format!("Error rendering graphic: {}", e))?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or("The graphic has no area to render.")?;
+    resvg::render(&tree, resvg::tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap.save_png(png).map_err(|e| format!("Error writing {}: {}", png.display(), e))
+}
+
+/// Inlines an image, so the SVG doesn't depend on the assets directory.
+fn data_uri(path: &Path) -> Result<String, String> {
+    let data = std::fs::read(path).map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    let mime = match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "webp" => "image/webp",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        _ => return Err(format!("{} isn't a png, jpeg, webp, gif or svg image", path.display()))
+    };
+    Ok(format!("data:{};base64,{}", mime, base64::engine::general_purpose::STANDARD.encode(data)))
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn portraits_stay_inside_assets() {
+        let dir = std::env::temp_dir().join(format!("rpipr-portraits-{}", std::process::id()));
+        let assets = dir.join("assets");
+        std::fs::create_dir_all(assets.join("mains")).unwrap();
+        for file in ["assets/mains/fox.png", "assets/A _ B.png", "outside.png"] {
+            std::fs::write(dir.join(file), b"").unwrap();
+        }
+
+        let template = GraphicTemplate {
+            assets: assets.clone(),
+            portraits: BTreeMap::from([
+                ("Fox".to_string(), PathBuf::from("mains/fox.png")),
+                ("Up".to_string(), PathBuf::from("../outside.png")),
+                ("Absolute".to_string(), dir.join("outside.png"))
+            ]),
+            ..GraphicTemplate::default()
+        };
+        let inside = assets.canonicalize().unwrap();
+        assert_eq!(template.portrait("Fox"), Ok(Some(inside.join("mains/fox.png"))));
+        assert!(template.portrait("Up").is_err());
+        assert!(template.portrait("Absolute").is_err());
+        assert_eq!(template.portrait("A / B"), Ok(Some(assets.join("A _ B.png"))));
+        assert_eq!(template.portrait("../outside"), Ok(None));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
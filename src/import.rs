@@ -0,0 +1,296 @@
+// Standings files for events that never ran on start.gg (paper brackets,
+// other sites' exports). List one in tournaments.txt or a season's `events`
+// like any slug; it's read every run, `--offline` included. A Challonge
+// tournament saved as JSON can be listed the same way (see challonge.rs).
+//
+// JSON:
+//
+//     {
+//         "name": "Weekly 12",
+//         "date": "2026-03-14",
+//         "entrants": 24,
+//         "standings": [{ "tag": "Player A", "placement": 1, "seed": 2 }, ...],
+//         "sets": [{ "winner": "Player A", "loser": "Player B",
+//                    "winner_score": 3, "loser_score": 1, "round": "Grand Final" }, ...]
+//     }
+//
+// CSV, `key,value` rows first, then a standings section and optionally a
+// sets section, each starting with its header row:
+//
+//     name,Weekly 12
+//     date,2026-03-14
+//     entrants,24
+//     tag,placement,seed
+//     Player A,1,2
+//     Player B,2,1
+//     winner,loser,winner_score,loser_score,round
+//     Player A,Player B,3,1,Grand Final
+//
+// `date`, `entrants`, seeds, scores, rounds and the sets are optional;
+// `entrants` defaults to the number of standings rows.
+
+use crate::challonge;
+use crate::prcalc::{EventData, EventSet, EventStanding, PlayerProfile};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ImportedEvent {
+    name: String,
+    date: Option<NaiveDate>,
+    entrants: Option<u32>,
+    standings: Vec<ImportedStanding>,
+    #[serde(default)]
+    sets: Vec<ImportedSet>
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ImportedStanding {
+    tag: String,
+    placement: u32,
+    seed: Option<u32>
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ImportedSet {
+    winner: String,
+    loser: String,
+    winner_score: Option<i32>,
+    loser_score: Option<i32>,
+    #[serde(default)]
+    round: String
+}
+
+/// Reads a `.csv` or `.json` standings file into the same shape a start.gg
+/// fetch produces, under `slug`.
+pub fn load_file(path: &Path, slug: &str) -> Result<EventData, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    let imported = match ext.as_str() {
+        "json" => match serde_json::from_str::<serde_json::Value>(&text) {
+            Ok(value) if challonge::is_export(&value) => return challonge::event_from_export(slug, value)
+                .map_err(|e| format!("Error in {}: {}", path.display(), e)),
+            Ok(value) => serde_json::from_value(value).map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string())
+        },
+        "csv" => parse_csv(&text),
+        _ => Err("only .csv and .json files can be imported".to_string())
+    };
+    imported.and_then(|i| i.into_event(slug))
+        .map_err(|e| format!("Error in {}: {}", path.display(), e))
+}
+
+#[derive(PartialEq)]
+enum Section {
+    Details,
+    Standings,
+    Sets
+}
+
+fn parse_csv(text: &str) -> Result<ImportedEvent, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .comment(Some(b'#'))
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+
+    let mut name = None;
+    let mut date = None;
+    let mut entrants = None;
+    let mut standings = vec![];
+    let mut sets = vec![];
+    let mut section = Section::Details;
+
+    for record in reader.records() {
+        let record = record.map_err(|e| e.to_string())?;
+        let line = record.position().map(|p| p.line()).unwrap_or(0);
+        let err = |message: String| format!("line {}: {}", line, message);
+        if record.iter().all(|field| field.is_empty()) { continue; }
+
+        let field = |idx: usize| record.get(idx).filter(|f| !f.is_empty());
+
+        match field(0) {
+            Some("tag") => { section = Section::Standings; continue; },
+            Some("winner") => { section = Section::Sets; continue; },
+            _ => {}
+        }
+        match section {
+            Section::Details => {
+                let value = field(1).ok_or_else(|| err("expected a key,value row".to_string()))?;
+                match field(0).unwrap_or("") {
+                    "name" => name = Some(value.to_string()),
+                    "date" => date = Some(NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                        .map_err(|_| err(format!("date \"{}\" isn't YYYY-MM-DD", value)))?),
+                    "entrants" => entrants = Some(value.parse::<u32>()
+                        .map_err(|_| err(format!("entrants \"{}\" isn't a number", value)))?),
+                    other => return Err(err(format!("unknown key \"{}\", expected name, date, entrants \
+                        or a tag,placement,seed header", other)))
+                }
+            },
+            Section::Standings => standings.push(ImportedStanding {
+                tag: field(0).unwrap_or("").to_string(),
+                placement: number(&record, 1, "placement").map_err(err)?.ok_or_else(|| err("missing placement".to_string()))?,
+                seed: number(&record, 2, "seed").map_err(err)?
+            }),
+            Section::Sets => sets.push(ImportedSet {
+                winner: field(0).unwrap_or("").to_string(),
+                loser: field(1).unwrap_or("").to_string(),
+                winner_score: number(&record, 2, "winner_score").map_err(err)?,
+                loser_score: number(&record, 3, "loser_score").map_err(err)?,
+                round: field(4).unwrap_or("").to_string()
+            })
+        }
+    }
+
+    Ok(ImportedEvent {
+        name: name.ok_or("missing the name row")?,
+        date,
+        entrants,
+        standings,
+        sets
+    })
+}
+
+/// An optional numeric column; empty cells are `None`.
+fn number<T: FromStr>(record: &csv::StringRecord, idx: usize, what: &str) -> Result<Option<T>, String> {
+    match record.get(idx).filter(|f| !f.is_empty()) {
+        Some(f) => f.parse().map(Some).map_err(|_| format!("{} \"{}\" isn't a number", what, f)),
+        None => Ok(None)
+    }
+}
+
+impl ImportedEvent {
+    fn into_event(self, slug: &str) -> Result<EventData, String> {
+        if self.standings.is_empty() {
+            return Err("no standings".to_string());
+        }
+        let mut tags = HashSet::new();
+        for s in &self.standings {
+            if s.tag.is_empty() {
+                return Err(format!("a standing at placement {} has no tag", s.placement));
+            }
+            if s.placement == 0 {
+                return Err(format!("{}'s placement must be 1 or more", s.tag));
+            }
+            if !tags.insert(s.tag.as_str()) {
+                return Err(format!("{} is in the standings twice", s.tag));
+            }
+        }
+        for set in &self.sets {
+            for tag in [&set.winner, &set.loser] {
+                if !tags.contains(tag.as_str()) {
+                    return Err(format!("set {} vs {}: \"{}\" isn't in the standings", set.winner, set.loser, tag));
+                }
+            }
+        }
+        let num_entrants = self.entrants.unwrap_or(self.standings.len() as u32);
+        if (num_entrants as usize) < self.standings.len() {
+            return Err(format!("{} entrants, but {} standings", num_entrants, self.standings.len()));
+        }
+        for s in &self.standings {
+            if s.placement > num_entrants {
+                return Err(format!("{} placed {} of {} entrants", s.tag, s.placement, num_entrants));
+            }
+            if let Some(seed) = s.seed.filter(|seed| *seed == 0 || *seed > num_entrants) {
+                return Err(format!("{}'s seed {} isn't between 1 and {}", s.tag, seed, num_entrants));
+            }
+        }
+
+        Ok(EventData {
+            slug: slug.to_string(),
+            name: self.name,
+            num_entrants,
+            start_date: self.date,
+            videogame: None,
+            standings: self.standings.into_iter().map(|s| EventStanding {
+                name: s.tag,
+                player_id: None,
+                placement: s.placement,
+                seed: s.seed,
+                team: None,
+                profile: PlayerProfile::default()
+            }).collect(),
+            sets: self.sets.into_iter().map(|s| EventSet {
+                round: s.round,
+                winner: s.winner,
+                winner_id: None,
+                loser: s.loser,
+                loser_id: None,
+                winner_score: s.winner_score,
+                loser_score: s.loser_score
+            }).collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DETAILS: &str = "name,Weekly 12\ndate,2026-03-14\n";
+
+    #[test]
+    fn reads_every_section() {
+        let csv = format!("{}entrants,3\n# a comment\ntag,placement,seed\nA,1,2\nB,2,\nC,3,1\n\n\
+            winner,loser,winner_score,loser_score,round\nA,B,3,1,Grand Final\nB,C,-1,0\n", DETAILS);
+        let event = parse_csv(&csv).unwrap().into_event("local/w12.csv").unwrap();
+        assert_eq!((event.name.as_str(), event.num_entrants), ("Weekly 12", 3));
+        assert_eq!(event.start_date, NaiveDate::from_ymd_opt(2026, 3, 14));
+        let standings: Vec<(&str, u32, Option<u32>)> = event.standings.iter()
+            .map(|s| (s.name.as_str(), s.placement, s.seed)).collect();
+        assert_eq!(standings, vec![("A", 1, Some(2)), ("B", 2, None), ("C", 3, Some(1))]);
+        assert_eq!(event.sets[0].round, "Grand Final");
+        // a DQ is reported as a negative score
+        assert_eq!((event.sets[1].winner_score, event.sets[1].loser_score), (Some(-1), Some(0)));
+        assert_eq!(event.sets[1].round, "");
+    }
+
+    #[test]
+    fn missing_columns() {
+        let csv = format!("{}tag,placement\nA\n", DETAILS);
+        assert_eq!(parse_csv(&csv).unwrap_err(), "line 4: missing placement");
+        assert_eq!(parse_csv("name\n").unwrap_err(), "line 1: expected a key,value row");
+        assert_eq!(parse_csv("tag,placement\nA,1\n").unwrap_err(), "missing the name row");
+
+        // seeds and set scores may be left out
+        let csv = format!("{}tag,placement\nA,1\nB,2\nwinner,loser\nA,B\n", DETAILS);
+        let event = parse_csv(&csv).unwrap().into_event("local/w12.csv").unwrap();
+        assert_eq!(event.standings[1].seed, None);
+        assert_eq!((event.sets[0].winner_score, event.sets[0].loser_score), (None, None));
+    }
+
+    #[test]
+    fn extra_columns_are_ignored() {
+        let csv = format!("{}tag,placement,seed,notes\nA,1,1,from pools\nB,2,2\n", DETAILS);
+        let event = parse_csv(&csv).unwrap().into_event("local/w12.csv").unwrap();
+        assert_eq!(event.standings.len(), 2);
+        assert_eq!(event.standings[0].seed, Some(1));
+    }
+
+    #[test]
+    fn bad_values() {
+        let csv = format!("{}tag,placement\nA,first\n", DETAILS);
+        assert_eq!(parse_csv(&csv).unwrap_err(), "line 4: placement \"first\" isn't a number");
+        assert_eq!(parse_csv("name,W\ndate,14/03/2026\n").unwrap_err(), "line 2: date \"14/03/2026\" isn't YYYY-MM-DD");
+        assert_eq!(parse_csv("name,W\nvenue,Union\n").unwrap_err(),
+            "line 2: unknown key \"venue\", expected name, date, entrants or a tag,placement,seed header");
+
+        let csv = format!("{}tag,placement\nA,1\nwinner,loser\nA,Z\n", DETAILS);
+        assert_eq!(parse_csv(&csv).unwrap().into_event("local/w12.csv").unwrap_err(),
+            "set A vs Z: \"Z\" isn't in the standings");
+
+        let csv = format!("{}entrants,2\ntag,placement\nA,1\nB,3\n", DETAILS);
+        assert_eq!(parse_csv(&csv).unwrap().into_event("local/w12.csv").unwrap_err(), "B placed 3 of 2 entrants");
+        let csv = format!("{}tag,placement,seed\nA,1,1\nB,2,3\n", DETAILS);
+        assert_eq!(parse_csv(&csv).unwrap().into_event("local/w12.csv").unwrap_err(),
+            "B's seed 3 isn't between 1 and 2");
+    }
+}
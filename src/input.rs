@@ -0,0 +1,322 @@
+// Parser for tournaments.txt.
+//
+// Every non-blank line names one event, optionally followed by options:
+//
+//     # full-line comments and trailing comments are ignored
+//     tournament/foo/event/ultimate-singles
+//     https://www.start.gg/tournament/foo/event/ultimate-singles/overview
+//     tournament/bar                     game=1386   # picks bar's Ultimate event
+//     paper/melee-4.csv                  game=1      # the game, for sites that don't say
+//     tournament/baz/event/singles       tier=A weight=1.2
+//     tournament/qux/event/singles       major   # same as tier=S
+//     https://challonge.com/abc123               # Challonge, see challonge.rs
+//     challonge/club-abc123                      # same as club.challonge.com/abc123
+//     paper/weekly-12.csv                tier=C  # imported standings, see import.rs
+
+use std::fmt;
+use std::path::PathBuf;
+use crate::config::Tier;
+use crate::constants::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventTarget {
+    /// An exact `tournament/x/event/y` slug.
+    Event(String),
+    /// A `tournament/x` slug, expanded to its event for `videogame_id`.
+    Tournament { slug: String, videogame_id: u32 },
+    /// A local `.csv` or `.json` standings file. `slug` is `local/<path>` as
+    /// written, so the event keeps its identity wherever it's run from.
+    File { path: PathBuf, slug: String },
+    /// A `challonge/<id>` slug; club brackets' ids are `<subdomain>-<url>`.
+    Challonge(String)
+}
+
+impl EventTarget {
+    pub fn slug(&self) -> &str {
+        match self {
+            EventTarget::Event(slug) => slug,
+            EventTarget::Tournament { slug, .. } => slug,
+            EventTarget::File { slug, .. } => slug,
+            EventTarget::Challonge(slug) => slug
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventEntry {
+    pub line: usize,
+    pub target: EventTarget,
+    pub tier: Option<Tier>,
+    pub weight: f64,
+    /// The videogame ID given with `game=`, if any.
+    pub game: Option<u32>
+}
+
+impl EventEntry {
+    pub fn from_slug(slug: impl Into<String>) -> Self {
+        EventEntry {
+            line: 0,
+            target: EventTarget::Event(slug.into()),
+            tier: None,
+            weight: 1.0,
+            game: None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputError {
+    pub line: usize,
+    pub message: String
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Parses every line of an input file, returning either all entries or every
+/// malformed line, so that a TO can fix the whole file in one go.
+pub fn parse_event_list(lines: &[String]) -> Result<Vec<EventEntry>, Vec<InputError>> {
+    let mut entries = vec![];
+    let mut errors = vec![];
+
+    for (idx, line) in lines.iter().enumerate() {
+        match parse_line(idx + 1, line) {
+            Ok(Some(entry)) => entries.push(entry),
+            Ok(None) => {},
+            Err(e) => errors.push(e)
+        }
+    }
+
+    if errors.is_empty() { Ok(entries) } else { Err(errors) }
+}
+
+pub fn parse_line(line_number: usize, line: &str) -> Result<Option<EventEntry>, InputError> {
+    let err = |message: String| InputError { line: line_number, message };
+
+    let line = strip_comment(line).trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+
+    let mut words = line.split_whitespace();
+    let location = words.next().unwrap();
+    let mut target = parse_location(location).map_err(err)?;
+
+    let mut weight: f64 = 1.0;
+    let mut tier: Option<Tier> = None;
+    let mut game: Option<u32> = None;
+    for option in words {
+        let (key, value) = match option.split_once('=') {
+            Some((k, v)) => (k, Some(v)),
+            None => (option, None)
+        };
+        match (key, value) {
+            ("major", None) => tier = Some(Tier::S),
+            ("tier", Some(v)) => {
+                tier = match v.parse::<Tier>() {
+                    Ok(t) => Some(t),
+                    Err(e) => return Err(err(e))
+                };
+            },
+            ("weight", Some(v)) => {
+                weight = match v.parse::<f64>() {
+                    Ok(w) if w.is_finite() && w >= 0.0 => w,
+                    _ => return Err(err(format!("weight must be a non-negative number, got \"{}\"", v)))
+                };
+            },
+            ("game", Some(v)) => {
+                game = match v.parse::<u32>() {
+                    Ok(g) => Some(g),
+                    Err(_) => return Err(err(format!("game must be a numeric start.gg videogame ID, got \"{}\"", v)))
+                };
+            },
+            ("weight" | "game" | "tier", None) => {
+                return Err(err(format!("option \"{}\" needs a value, like {}=...", key, key)));
+            },
+            ("major", Some(_)) => {
+                return Err(err("option \"major\" doesn't take a value".to_string()));
+            },
+            _ => return Err(err(format!("unknown option \"{}\"", option)))
+        }
+    }
+
+    if let (Some(g), EventTarget::Tournament { videogame_id, .. }) = (game, &mut target) {
+        *videogame_id = g;
+    }
+
+    Ok(Some(EventEntry { line: line_number, target, tier, weight, game }))
+}
+
+/// `#` starts a comment at the beginning of a line or after whitespace, so
+/// URL fragments survive.
+fn strip_comment(line: &str) -> &str {
+    let mut prev_is_space = true;
+    for (idx, c) in line.char_indices() {
+        if c == '#' && prev_is_space {
+            return &line[..idx];
+        }
+        prev_is_space = c.is_whitespace();
+    }
+    line
+}
+
+fn parse_location(location: &str) -> Result<EventTarget, String> {
+    let lower = location.to_lowercase();
+    if !location.contains("://") && (lower.ends_with(".csv") || lower.ends_with(".json")) {
+        return Ok(EventTarget::File {
+            path: PathBuf::from(location),
+            slug: format!("local/{}", location.trim_start_matches("./"))
+        });
+    }
+
+    let path = match location.split_once("://") {
+        Some((_, rest)) => rest,
+        None => location
+    };
+    let path = path.split(['?', '#']).next().unwrap();
+
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    if let Some(target) = parse_challonge(&segments) {
+        return target.ok_or_else(|| format!("\"{}\" is missing its Challonge tournament", location));
+    }
+    let start = match segments.iter().position(|s| *s == "tournament") {
+        Some(i) => i,
+        None => return Err(format!(
+            "\"{}\" is not an event slug, tournament slug or start.gg URL", location))
+    };
+
+    if start > 0 {
+        let host = segments[0].trim_start_matches("www.");
+        if start != 1 || !STARTGG_HOSTS.contains(&host) {
+            return Err(format!("\"{}\" is not a start.gg URL", location));
+        }
+    }
+
+    match &segments[start..] {
+        ["tournament", t] => Ok(EventTarget::Tournament {
+            slug: format!("tournament/{}", t),
+            videogame_id: DEFAULT_VIDEOGAME_ID
+        }),
+        ["tournament", t, "event", e, ..] => Ok(EventTarget::Event(format!("tournament/{}/event/{}", t, e))),
+        // start.gg tournament pages have tabs like /details or /events
+        ["tournament", t, tab] if *tab != "event" => Ok(EventTarget::Tournament {
+            slug: format!("tournament/{}", t),
+            videogame_id: DEFAULT_VIDEOGAME_ID
+        }),
+        _ => Err(format!("\"{}\" is missing its tournament or event name", location))
+    }
+}
+
+/// `challonge/<id>` or a challonge.com URL, or `None` for anything else.
+fn parse_challonge(segments: &[&str]) -> Option<Option<EventTarget>> {
+    let host = segments.first()?.trim_start_matches("www.");
+    let id = if host == "challonge" {
+        segments.get(1).map(|id| id.to_string())
+    } else if host == CHALLONGE_HOST {
+        // challonge.com/fr/abc123 is the same bracket in another language, and
+        // challonge.com/abc123/standings one of its tabs
+        let skip = if segments.len() > 2 && is_language(segments[1]) { 2 } else { 1 };
+        segments.get(skip).map(|id| id.to_string())
+    } else if let Some(subdomain) = host.strip_suffix(&format!(".{}", CHALLONGE_HOST)) {
+        segments.get(1).map(|id| format!("{}-{}", subdomain, id))
+    } else {
+        return None;
+    };
+    Some(id.map(|id| EventTarget::Challonge(format!("challonge/{}", id))))
+}
+
+/// Challonge's language prefixes, like `fr` or `pt_BR`.
+fn is_language(segment: &str) -> bool {
+    CHALLONGE_LANGUAGES.contains(&segment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(line: &str) -> EventEntry {
+        parse_line(1, line).unwrap().unwrap()
+    }
+
+    fn error(line: &str) -> String {
+        parse_line(3, line).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn comments() {
+        assert_eq!(strip_comment("# a whole line"), "");
+        assert_eq!(strip_comment("tournament/foo # trailing"), "tournament/foo ");
+        assert_eq!(strip_comment("https://www.start.gg/tournament/foo#top"), "https://www.start.gg/tournament/foo#top");
+        assert_eq!(parse_line(1, "   # indented").unwrap(), None);
+        assert_eq!(parse_line(1, "").unwrap(), None);
+    }
+
+    #[test]
+    fn locations() {
+        let event = EventTarget::Event("tournament/foo/event/ultimate-singles".to_string());
+        assert_eq!(parse_location("tournament/foo/event/ultimate-singles"), Ok(event.clone()));
+        assert_eq!(parse_location("https://www.start.gg/tournament/foo/event/ultimate-singles/overview"), Ok(event.clone()));
+        assert_eq!(parse_location("start.gg/tournament/foo/event/ultimate-singles?tab=bracket"), Ok(event));
+
+        let tournament = EventTarget::Tournament { slug: "tournament/foo".to_string(), videogame_id: DEFAULT_VIDEOGAME_ID };
+        assert_eq!(parse_location("tournament/foo"), Ok(tournament.clone()));
+        assert_eq!(parse_location("https://start.gg/tournament/foo/details"), Ok(tournament));
+
+        assert_eq!(parse_location("./paper/weekly-12.CSV"), Ok(EventTarget::File {
+            path: PathBuf::from("./paper/weekly-12.CSV"),
+            slug: "local/paper/weekly-12.CSV".to_string()
+        }));
+
+        assert!(parse_location("https://example.com/tournament/foo").is_err());
+        assert!(parse_location("tournament/foo/event").is_err());
+        assert!(parse_location("foo/bar").is_err());
+    }
+
+    #[test]
+    fn options() {
+        let e = entry("tournament/foo/event/singles tier=A weight=1.2  # a comment");
+        assert_eq!((e.tier, e.weight, e.game), (Some(Tier::A), 1.2, None));
+        assert_eq!(entry("tournament/foo/event/singles major").tier, Some(Tier::S));
+
+        let e = entry("tournament/bar game=1");
+        assert_eq!(e.target, EventTarget::Tournament { slug: "tournament/bar".to_string(), videogame_id: 1 });
+        assert_eq!(e.game, Some(1));
+        assert_eq!(entry("paper/melee-4.csv game=1").game, Some(1));
+
+        assert_eq!(error("tournament/foo weight=-1"), "line 3: weight must be a non-negative number, got \"-1\"");
+        assert_eq!(error("tournament/foo game=melee"), "line 3: game must be a numeric start.gg videogame ID, got \"melee\"");
+        assert_eq!(error("tournament/foo tier=D"), "line 3: tier must be one of C, B, A or S, got \"D\"");
+        assert_eq!(error("tournament/foo tier"), "line 3: option \"tier\" needs a value, like tier=...");
+        assert_eq!(error("tournament/foo major=yes"), "line 3: option \"major\" doesn't take a value");
+        assert_eq!(error("tournament/foo bracket=1"), "line 3: unknown option \"bracket=1\"");
+    }
+
+    #[test]
+    fn every_bad_line_is_reported() {
+        let lines: Vec<String> = ["tournament/foo", "nonsense", "tournament/bar weight=x"]
+            .iter().map(|l| l.to_string()).collect();
+        let errors = parse_event_list(&lines).unwrap_err();
+        assert_eq!(errors.iter().map(|e| e.line).collect::<Vec<_>>(), vec![2, 3]);
+    }
+
+    fn challonge(location: &str) -> EventTarget {
+        parse_location(location).unwrap()
+    }
+
+    #[test]
+    fn challonge_urls() {
+        assert_eq!(challonge("https://challonge.com/fr/abc123"), EventTarget::Challonge("challonge/abc123".to_string()));
+        assert_eq!(challonge("https://challonge.com/pt_BR/abc123"), EventTarget::Challonge("challonge/abc123".to_string()));
+        assert_eq!(challonge("https://challonge.com/abc123/standings"), EventTarget::Challonge("challonge/abc123".to_string()));
+        assert_eq!(challonge("challonge.com/abc123/module"), EventTarget::Challonge("challonge/abc123".to_string()));
+        assert_eq!(challonge("challonge.com/ab/standings"), EventTarget::Challonge("challonge/ab".to_string()));
+        assert_eq!(challonge("https://sub.challonge.com/abc123"), EventTarget::Challonge("challonge/sub-abc123".to_string()));
+        assert_eq!(challonge("challonge/club-abc123"), EventTarget::Challonge("challonge/club-abc123".to_string()));
+        assert!(parse_location("https://challonge.com").is_err());
+    }
+}
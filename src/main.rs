@@ -1,14 +1,39 @@
+mod challonge;
+mod config;
 mod constants;
+mod diff;
+mod discover;
+mod eligibility;
+mod export;
+mod graphic;
+mod import;
+mod input;
+mod placements;
+mod player;
 mod prcalc;
 mod query;
-
-use futures::executor::block_on;
-use indicatif::{MultiProgress, ProgressStyle};
+mod report;
+mod serve;
+mod source;
+mod store;
+mod tui;
+
+use challonge::Challonge;
+use chrono::NaiveDate;
+use clap::{Parser, Subcommand};
+use config::{FieldStrengthMode, GameConfig, ProjectConfig, QualificationConfig, ScoringConfig, SeasonConfig, TeamMode};
+use eligibility::Eligibility;
+use export::{OutputFormat, RankingResults};
+use indicatif::{MultiProgress, ProgressDrawTarget, ProgressStyle};
+use input::{EventEntry, EventTarget};
 use prcalc::PowerRankings;
-use query::do_query;
+use query::{QueryError, StartGg};
+use source::{Fixtures, TournamentSource};
+use std::collections::HashSet;
 use std::{fs::File, process::exit};
-use std::io::{self, BufRead, Write};
-use std::path::Path;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+use store::Store;
 
 // borrowed from the rust handbook
 fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
@@ -17,11 +42,122 @@ where P: AsRef<Path>, {
     Ok(io::BufReader::new(file).lines())
 }
 
+#[derive(Parser, Debug)]
+#[command(about = "Power Rankings calculator for Rensselaer Smash")]
+#[command(args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    rank: RankArgs
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Calculate the PR from tournaments.txt (the default)
+    Rank(RankArgs),
+    /// Search start.gg for candidate events and write them out for review
+    Discover(discover::DiscoverArgs),
+    /// Render the overall PR's top N as an SVG and PNG graphic
+    ExportGraphic(graphic::GraphicArgs),
+    /// Explain one player's ranking from saved results
+    Player(player::PlayerArgs),
+    /// Compare two saved results, e.g. last season's against this one's
+    Diff(diff::DiffArgs),
+    /// Serve saved rankings as JSON over HTTP
+    Serve(serve::ServeArgs),
+    /// Explore saved rankings in the terminal, trying out rule changes
+    Tui(tui::TuiArgs)
+}
+
+#[derive(clap::Args, Debug)]
+struct RankArgs {
+    /// Fail the run if any listed event couldn't be loaded
+    #[arg(long)]
+    strict: bool,
+
+    /// Rank a season from the project file instead of tournaments.txt
+    #[arg(long)]
+    season: Option<String>,
+
+    /// Project file holding the season definitions
+    #[arg(long, default_value = "pr.toml")]
+    config: PathBuf,
+
+    /// Measure time decay from this date instead of the season's end (YYYY-MM-DD)
+    #[arg(long, value_parser = discover::parse_date)]
+    as_of: Option<NaiveDate>,
+
+    /// Output format for the rankings
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// Where to write the rankings (default output.txt, .json, .csv, .md or .html)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Template for the markdown and html reports, in place of the built-in one
+    #[arg(long)]
+    template: Option<PathBuf>,
+
+    /// Database that fetched events are saved to and ranked from
+    #[arg(long, default_value = "pr.db")]
+    db: PathBuf,
+
+    /// Rank from events already in the database instead of fetching them
+    #[arg(long)]
+    offline: bool,
+
+    /// How doubles teams are ranked, overriding the season's `teams`
+    #[arg(long, value_enum)]
+    teams: Option<TeamMode>,
+
+    /// Answer start.gg and Challonge requests from canned responses in this
+    /// directory, for testing
+    #[arg(long)]
+    fixtures: Option<PathBuf>
+}
+
 #[tokio::main]
 async fn main() {
-    let procbars = MultiProgress::new();
-    let mut pr: PowerRankings = PowerRankings::new();
+    let cli = Cli::parse();
+    match cli.command {
+        None => rank(cli.rank).await,
+        Some(Command::Rank(args)) => rank(args).await,
+        Some(Command::Discover(args)) => {
+            match discover::discover(args).await {
+                Ok(n) => println!("Found {} candidate event(s).", n),
+                Err(e) => {
+                    println!("{}", e);
+                    exit(1);
+                }
+            }
+        },
+        Some(Command::ExportGraphic(args)) => {
+            if let Err(e) = graphic::export_graphic(args) {
+                println!("{}", e);
+                exit(1);
+            }
+        },
+        Some(Command::Player(args)) => {
+            if let Err(e) = player::player_report(args) {
+                println!("{}", e);
+                exit(1);
+            }
+        },
+        Some(Command::Diff(args)) => {
+            if let Err(e) = diff::diff(args) {
+                println!("{}", e);
+                exit(1);
+            }
+        },
+        Some(Command::Serve(args)) => serve(args).await,
+        Some(Command::Tui(args)) => explore(args).await
+    }
+}
 
+fn read_tournaments_txt() -> Vec<EventEntry> {
     let inputs = match read_lines("tournaments.txt") {
         Ok(f) => f,
         Err(e) => {
@@ -30,169 +166,551 @@ async fn main() {
         }
     };
 
-    let lines: Vec<String> = inputs.flatten().collect();
-    let tournament_gather_bar = procbars.add(indicatif::ProgressBar::new(lines.len() as u64));
-    tournament_gather_bar.set_style(ProgressStyle::with_template(
-        "Getting Tournament Data  {pos:>3}/{len:3} {bar:>30.cyan}"
-    ).unwrap());
-    for line in lines {
-        if line == "" { continue; }
-        // println!("Tournament: {}", line);
-
-        block_on(do_query(query::TournamentQueryVariables {
-            event_slug: Some(line.clone())
-        },
-        &mut pr)).unwrap();
-        tournament_gather_bar.inc(1);
+    let lines: Vec<String> = inputs.map_while(Result::ok).collect();
+    match input::parse_event_list(&lines) {
+        Ok(e) => e,
+        Err(errors) => {
+            for e in errors {
+                println!("tournaments.txt {}", e);
+            }
+            exit(1);
+        }
     }
-    tournament_gather_bar.finish();
+}
 
-    let mut outfile = match File::create("output.txt") {
-        Ok(f) => f,
+/// Loads a season from the project file, along with every event it lists or,
+/// if `discover` is set, discovers. Callers say why when they don't discover.
+async fn read_season(config_path: &Path, name: &str, discover: bool) -> (SeasonConfig, Vec<EventEntry>) {
+    let project = match ProjectConfig::load(config_path) {
+        Ok(p) => p,
         Err(e) => {
-            println!("Error creating output file: {}", e);
+            println!("{}", e);
+            exit(1);
+        }
+    };
+    let season = match project.season(name) {
+        Some(s) => s.clone(),
+        None => {
+            let names: Vec<&str> = project.seasons.iter().map(|s| s.name.as_str()).collect();
+            println!("No season named \"{}\" in {} (have: {})", name, config_path.display(), names.join(", "));
             exit(1);
         }
     };
 
-    /* ---- SECTION 0: QUALIFICATION CHECK ---- */
-
-    let qualified_player_bar = procbars.add(indicatif::ProgressBar::new(1));
-    qualified_player_bar.set_style(ProgressStyle::with_template(
-        "Checking who qualified   {pos:>3}/{len:3} {bar:>30.cyan}"
-    ).unwrap());
-
-    pr.get_only_qualified_players(&qualified_player_bar);
-    qualified_player_bar.finish();
-
-    /* ---- SECTION 1: AVERAGE PLACEMENTS ---- */
+    let base_dir = config_path.parent().unwrap_or(Path::new("."));
+    let mut entries = match season.event_entries(base_dir) {
+        Ok(e) => e,
+        Err(errors) => {
+            for e in errors {
+                println!("{}", e);
+            }
+            exit(1);
+        }
+    };
 
-    let score_avg_placement_bar = procbars.add(indicatif::ProgressBar::new(1));
-    score_avg_placement_bar.set_style(ProgressStyle::with_template(
-        "Scoring: Average Placement   {pos:>3}/{len:3} {bar:>30.cyan}"
-    ).unwrap());
+    if let Some(rules) = season.discover.as_ref().filter(|_| discover) {
+        match discover::discover_events(rules, season.start, season.end).await {
+            Ok(found) => entries.extend(found.into_iter().map(|e| EventEntry::from_slug(e.slug))),
+            Err(e) => {
+                println!("Error discovering events for season \"{}\": {}", season.name, e);
+                exit(1);
+            }
+        }
+    }
 
-    pr.calculate_scoring(constants::CalculationMethods::AveragePlacement, &score_avg_placement_bar);
+    (season, entries)
+}
 
-    score_avg_placement_bar.finish();
+async fn rank(args: RankArgs) {
+    let procbars = MultiProgress::new();
 
-    writeln!(outfile, "Average Placement Statistics").unwrap();
-    writeln!(outfile, "============================").unwrap();
-    pr.export_ordered(&mut outfile, prcalc::PROrder::LoFirst, 12, |placement, p, f| {
-        writeln!(f, "{:>3}  {:30}  AVG = {:5.4}", placement, p.get_name(), p.get_score()).unwrap();
-        // writeln!(f, "{:?}", p).unwrap();
+    // read up front so a typo doesn't throw away every fetched event
+    let template = args.template.as_ref().map(|path| match std::fs::read_to_string(path) {
+        Ok(t) => t,
+        Err(e) => {
+            println!("Error reading template {}: {}", path.display(), e);
+            exit(1);
+        }
     });
 
-    writeln!(outfile, "").unwrap();
-    writeln!(outfile, "").unwrap();
-
-    /* ---- SECTION 2: WEIGHTED POINTS ---- */
+    let (season, entries) = match &args.season {
+        Some(name) => {
+            let (season, entries) = read_season(&args.config, name, !args.offline).await;
+            if season.discover.is_some() && args.offline {
+                println!("Not searching start.gg for season \"{}\"'s events with --offline; ranking the listed ones.", season.name);
+            }
+            (Some(season), entries)
+        },
+        None => (None, read_tournaments_txt())
+    };
 
-    writeln!(outfile, "Weighted Points (based on minimum # entrants)").unwrap();
-    writeln!(outfile, "=============================================").unwrap();
+    let mut store = match Store::open(&args.db) {
+        Ok(s) => s,
+        Err(e) => {
+            println!("Error opening database {}: {}", args.db.display(), e);
+            exit(1);
+        }
+    };
 
-    let weighted_score_bar = procbars.add(indicatif::ProgressBar::new(1));
-    weighted_score_bar.set_style(ProgressStyle::with_template(
-        "Scoring: Weighted Points   {pos:>3}/{len:3} {bar:>30.cyan}"
+    let tournament_gather_bar = procbars.add(indicatif::ProgressBar::new(entries.len() as u64));
+    tournament_gather_bar.set_style(ProgressStyle::with_template(
+        "Getting Tournament Data  {pos:>3}/{len:3} {bar:>30.cyan}"
     ).unwrap());
+    let sources: Vec<Box<dyn TournamentSource>> = match &args.fixtures {
+        Some(dir) => vec![Box::new(Fixtures::new(dir))],
+        None => vec![Box::new(StartGg::from_env()), Box::new(Challonge::from_env())]
+    };
+    let mut failed_events: Vec<String> = vec![];
+    let mut saved_entries: Vec<&EventEntry> = vec![];
+    for entry in &entries {
+        tournament_gather_bar.inc(1);
+        let fetched = match &entry.target {
+            // imported files are cheap to reread, so they're picked up even offline
+            EventTarget::File { path, slug } => match import::load_file(path, slug) {
+                Ok(event) => Some(event),
+                Err(e) => {
+                    procbars.println(format!("Skipping event {}: {}", slug, e)).unwrap();
+                    failed_events.push(slug.clone());
+                    continue;
+                }
+            },
+            _ if args.offline => None,
+            // every remote target has a source
+            _ => match sources.iter().find(|s| s.handles(&entry.target)).unwrap().load_event(entry) {
+                Ok(event) => Some(event),
+                Err(QueryError::NoEventForGame { slug }) if tried_for_every_game(season.as_ref(), entry) => {
+                    if let EventTarget::Tournament { videogame_id, .. } = &entry.target {
+                        let game = season.iter().flat_map(|s| &s.games).find(|g| g.id == *videogame_id).unwrap();
+                        procbars.println(format!("No {} event at {}", game.name, slug)).unwrap();
+                    }
+                    continue;
+                },
+                Err(e) => {
+                    if e.is_auth() {
+                        tournament_gather_bar.abandon();
+                        println!("Aborting: {}", e);
+                        exit(1);
+                    }
+                    procbars.println(format!("Skipping event {}", e)).unwrap();
+                    failed_events.push(e.slug().to_string());
+                    continue;
+                }
+            }
+        };
+        if let Some(fetched) = fetched {
+            if let Err(e) = store.save_event(&fetched, entry) {
+                tournament_gather_bar.abandon();
+                println!("Error saving {} to {}: {}", fetched.slug, args.db.display(), e);
+                exit(1);
+            }
+        }
+        saved_entries.push(entry);
+    }
+    tournament_gather_bar.finish();
 
-    pr.calculate_scoring(constants::CalculationMethods::WeightedPoints, &weighted_score_bar);
-
-    weighted_score_bar.finish();
-
-    pr.export_ordered(&mut outfile, prcalc::PROrder::HiFirst, 12, |placement, p, f| {
-        writeln!(f, "{:>3}  {:30}  AVG = {:5.4}", placement, p.get_name(), p.get_score()).unwrap();
-        // writeln!(f, "{:?}", p).unwrap();
-    });
+    let settings = RankSettings {
+        season: season.as_ref(),
+        config: &args.config,
+        db: &args.db,
+        as_of: args.as_of,
+        teams: args.teams
+    };
+    let rankings = load_saved_events(&store, &saved_entries, &settings, &procbars, &mut failed_events);
+    report_failed_events(&failed_events);
+    if !failed_events.is_empty() && args.strict {
+        println!("Aborting because --strict was given.");
+        exit(1);
+    }
+    let results = score_rankings(rankings, &settings, &procbars);
 
-    writeln!(outfile, "").unwrap();
-    writeln!(outfile, "").unwrap();
+    /* ---- OUTPUT ---- */
 
-    /* ---- SECTION 3: MEDIAN POINTS ---- */
+    let output = args.output.clone().unwrap_or_else(|| PathBuf::from(args.format.default_output()));
+    let mut outfile = match File::create(&output) {
+        Ok(f) => f,
+        Err(e) => {
+            println!("Error creating output file {}: {}", output.display(), e);
+            exit(1);
+        }
+    };
+    if let Err(e) = export::write_results(&mut outfile, &results, args.format, template.as_deref()) {
+        println!("Error writing {}: {}", output.display(), e);
+        exit(1);
+    }
+}
 
-    writeln!(outfile, "Median Points Statistics").unwrap();
-    writeln!(outfile, "========================").unwrap();
+/// `serve` and `explore` read only the database, where discovered events
+/// aren't marked as the season's.
+fn skipping_discovery(season: &SeasonConfig) {
+    if season.discover.is_some() {
+        println!("Season \"{}\" discovers its events on start.gg; only the listed ones are read from the database.",
+            season.name);
+    }
+}
 
-    let median_score_bar = procbars.add(indicatif::ProgressBar::new(1));
-    median_score_bar.set_style(ProgressStyle::with_template(
-        "Scoring: Median Points   {pos:>3}/{len:3} {bar:>30.cyan}"
-    ).unwrap());
+/// Ranks every season from the database, then serves them (see serve.rs).
+async fn serve(args: serve::ServeArgs) {
+    let procbars = MultiProgress::new();
+    let store = match Store::open(&args.db) {
+        Ok(s) => s,
+        Err(e) => {
+            println!("Error opening database {}: {}", args.db.display(), e);
+            exit(1);
+        }
+    };
 
-    pr.calculate_scoring(constants::CalculationMethods::MedianPoints, &median_score_bar);
+    let mut seasons: Vec<(Option<SeasonConfig>, Vec<EventEntry>)> = vec![];
+    if args.config.exists() {
+        let project = match ProjectConfig::load(&args.config) {
+            Ok(p) => p,
+            Err(e) => {
+                println!("{}", e);
+                exit(1);
+            }
+        };
+        for season in &project.seasons {
+            let (season, entries) = read_season(&args.config, &season.name, false).await;
+            skipping_discovery(&season);
+            seasons.push((Some(season), entries));
+        }
+    } else {
+        seasons.push((None, read_tournaments_txt()));
+    }
 
-    median_score_bar.finish();
+    let mut served: Vec<serve::Season> = vec![];
+    for (season, entries) in &seasons {
+        let settings = RankSettings {
+            season: season.as_ref(),
+            config: &args.config,
+            db: &args.db,
+            as_of: None,
+            teams: None
+        };
+        let mut failed_events: Vec<String> = vec![];
+        let entries: Vec<&EventEntry> = entries.iter().collect();
+        let rankings = load_saved_events(&store, &entries, &settings, &procbars, &mut failed_events);
+        report_failed_events(&failed_events);
+        served.push(serve::Season {
+            name: season.as_ref().map(|s| s.name.clone()).unwrap_or_else(|| "default".to_string()),
+            start: season.as_ref().and_then(|s| s.start),
+            end: season.as_ref().and_then(|s| s.end),
+            results: score_rankings(rankings, &settings, &procbars)
+        });
+    }
 
-    pr.export_ordered(&mut outfile, prcalc::PROrder::HiFirst, 12, |placement, p, f| {
-        writeln!(f, "{:>3}  {:30}  AVG = {:5.4}", placement, p.get_name(), p.get_score()).unwrap();
-        // writeln!(f, "{:?}", p).unwrap();
-    });
+    if let Err(e) = serve::run(args.addr, served, store).await {
+        println!("{}", e);
+        exit(1);
+    }
+}
 
-    writeln!(outfile, "").unwrap();
-    writeln!(outfile, "").unwrap();
+/// Loads one game's events from the database and opens them in the TUI (see
+/// tui.rs).
+async fn explore(args: tui::TuiArgs) {
+    let procbars = MultiProgress::new();
+    let (season, entries) = match &args.season {
+        Some(name) => {
+            let (season, entries) = read_season(&args.config, name, false).await;
+            skipping_discovery(&season);
+            (Some(season), entries)
+        },
+        None => (None, read_tournaments_txt())
+    };
+    let store = match Store::open(&args.db) {
+        Ok(s) => s,
+        Err(e) => {
+            println!("Error opening database {}: {}", args.db.display(), e);
+            exit(1);
+        }
+    };
 
-    /* ---- SECTION 4: MEAN POINTS ---- */
+    let settings = RankSettings {
+        season: season.as_ref(),
+        config: &args.config,
+        db: &args.db,
+        as_of: None,
+        teams: None
+    };
+    let mut failed_events: Vec<String> = vec![];
+    let entries: Vec<&EventEntry> = entries.iter().collect();
+    let mut rankings = load_saved_events(&store, &entries, &settings, &procbars, &mut failed_events);
+    report_failed_events(&failed_events);
+
+    let games: Vec<&str> = rankings.iter().filter_map(|(g, _)| g.as_ref().map(|g| g.name.as_str())).collect();
+    let found = match &args.game {
+        Some(name) => rankings.iter().position(|(g, _)| g.as_ref().is_some_and(|g| g.name.eq_ignore_ascii_case(name))),
+        None if rankings.len() == 1 => Some(0),
+        None => {
+            println!("This season ranks several games, pick one with --game (have: {})", games.join(", "));
+            exit(1);
+        }
+    };
+    let (game, pr) = match found {
+        Some(i) => rankings.swap_remove(i),
+        None => {
+            println!("No game named \"{}\" in this season (have: {})", args.game.unwrap(), games.join(", "));
+            exit(1);
+        }
+    };
 
-    writeln!(outfile, "Mean Points Statistics").unwrap();
-    writeln!(outfile, "======================").unwrap();
+    // rescoring happens behind the TUI, so its progress bars stay hidden
+    let hidden = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+    let score = |pr| score_rankings(vec![(game.clone(), pr)], &settings, &hidden).pop().unwrap();
+    if let Err(e) = tui::run(pr, score) {
+        println!("Error running the terminal UI: {}", e);
+        exit(1);
+    }
+}
 
-    let mean_score_bar = procbars.add(indicatif::ProgressBar::new(1));
-    mean_score_bar.set_style(ProgressStyle::with_template(
-        "Scoring: Mean Points   {pos:>3}/{len:3} {bar:>30.cyan}"
-    ).unwrap());
+/// How saved events are ranked, shared by `rank`, `serve` and `tui`.
+struct RankSettings<'a> {
+    season: Option<&'a SeasonConfig>,
+    /// The project file, which rosters and prior rankings are relative to.
+    config: &'a Path,
+    db: &'a Path,
+    as_of: Option<NaiveDate>,
+    teams: Option<TeamMode>
+}
 
-    pr.calculate_scoring(constants::CalculationMethods::MeanPoints, &mean_score_bar);
+/// Tournaments listed without game= in a season of several games are tried
+/// for every game, and needn't have an event for them all.
+fn tried_for_every_game(season: Option<&SeasonConfig>, entry: &EventEntry) -> bool {
+    season.is_some_and(|s| s.games.len() > 1)
+        && entry.game.is_none()
+        && matches!(entry.target, EventTarget::Tournament { .. })
+}
 
-    mean_score_bar.finish();
+fn report_failed_events(failed_events: &[String]) {
+    if !failed_events.is_empty() {
+        println!("{} event(s) could not be loaded and are missing from the PR:", failed_events.len());
+        for slug in failed_events {
+            println!("    {}", slug);
+        }
+    }
+}
 
-    pr.export_ordered(&mut outfile, prcalc::PROrder::HiFirst, 12, |placement, p, f| {
-        writeln!(f, "{:>3}  {:30}  AVG = {:5.4}", placement, p.get_name(), p.get_score()).unwrap();
-        // writeln!(f, "{:?}", p).unwrap();
+/// Reads `entries` back from the database into one `PowerRankings` per game,
+/// or a single one if the season doesn't list games. Events that aren't
+/// there are added to `failed_events`.
+fn load_saved_events(store: &Store, entries: &[&EventEntry], settings: &RankSettings, procbars: &MultiProgress,
+        failed_events: &mut Vec<String>) -> Vec<(Option<GameConfig>, PowerRankings)> {
+    let season = settings.season;
+    let eligibility = season.and_then(|s| s.eligibility.as_ref()).map(|config| {
+        match Eligibility::load(config, settings.config.parent().unwrap_or(Path::new("."))) {
+            Ok(e) => e,
+            Err(e) => {
+                println!("{}", e);
+                exit(1);
+            }
+        }
     });
 
-    writeln!(outfile, "").unwrap();
-    writeln!(outfile, "").unwrap();
-
-    /* ---- SECTION 5: UNWEIGHTED POINTS ---- */
-
-    writeln!(outfile, "Unweighted Points Statistics").unwrap();
-    writeln!(outfile, "============================").unwrap();
+    // one ranking per game the season lists, or a single one for everything
+    let games: Vec<Option<GameConfig>> = match season {
+        Some(s) if !s.games.is_empty() => s.games.iter().cloned().map(Some).collect(),
+        _ => vec![None]
+    };
+    let mut rankings: Vec<(Option<GameConfig>, PowerRankings)> = games.into_iter().map(|game| {
+        let (mut scoring, qualification) = match season {
+            Some(s) => s.rules(game.as_ref()),
+            None => (ScoringConfig::default(), QualificationConfig::default())
+        };
+        if let Some(decay) = &mut scoring.decay {
+            decay.reference = settings.as_of
+                .or(decay.reference)
+                .or(season.and_then(|s| s.end));
+        }
+        if let Some(teams) = settings.teams {
+            scoring.teams = teams;
+        }
+        let mut pr = PowerRankings::with_config(scoring, qualification);
+        if let Some(eligibility) = &eligibility {
+            pr.set_eligibility(eligibility.clone());
+        }
+        (game, pr)
+    }).collect();
+
+    // rank from the database either way, once everything is saved, so tag
+    // changes are merged even on the run that first sees them
+    let mut loaded_events: HashSet<String> = HashSet::new();
+    let mut found_tournaments: HashSet<&str> = HashSet::new();
+    let mut missing_tournaments: Vec<&str> = vec![];
+    for entry in entries.iter().copied() {
+        let event = match store.cached_event(entry) {
+            Ok(Some(event)) => event,
+            Ok(None) if tried_for_every_game(season, entry) => {
+                missing_tournaments.push(entry.target.slug());
+                continue;
+            },
+            Ok(None) => {
+                procbars.println(format!("Skipping event {}: it isn't in {} yet, run without --offline to fetch it",
+                    entry.target.slug(), settings.db.display())).unwrap();
+                failed_events.push(entry.target.slug().to_string());
+                continue;
+            },
+            Err(e) => {
+                println!("Error reading {} from {}: {}", entry.target.slug(), settings.db.display(), e);
+                exit(1);
+            }
+        };
+
+        if let EventTarget::Tournament { slug, .. } = &entry.target {
+            found_tournaments.insert(slug);
+        }
+        // an event can be both listed and discovered; the listed entry wins
+        if !loaded_events.insert(event.slug.clone()) { continue; }
+
+        if let (Some(season), Some(date)) = (season, event.start_date) {
+            if !season.contains(date) {
+                procbars.println(format!("Skipping event {}: it started {}, outside season \"{}\"",
+                    event.slug, date, season.name)).unwrap();
+                continue;
+            }
+        }
 
-    let unweighted_score_bar = procbars.add(indicatif::ProgressBar::new(1));
-    unweighted_score_bar.set_style(ProgressStyle::with_template(
-        "Scoring: Unweighted Points   {pos:>3}/{len:3} {bar:>30.cyan}"
-    ).unwrap());
+        let pr = match rankings.iter_mut().find(|(game, _)| game.as_ref().is_none_or(|g| Some(g.id) == event.videogame)) {
+            Some((_, pr)) => pr,
+            None => {
+                let reason = match event.videogame {
+                    Some(id) => format!("its game ({}) isn't one of season \"{}\"'s games", id, season.unwrap().name),
+                    None => "it doesn't say which game it's for, give it a game=".to_string()
+                };
+                procbars.println(format!("Skipping event {}: {}", event.slug, reason)).unwrap();
+                continue;
+            }
+        };
+
+        let overrides = season.and_then(|s| s.event_settings.get(&event.slug));
+        let tier = overrides.and_then(|o| o.tier).or(entry.tier);
+        let weight = overrides.and_then(|o| o.weight).unwrap_or(entry.weight);
+        let id = pr.add_event(&event, tier, weight);
+        let excluded = &pr.tournaments()[id as usize].excluded;
+        if !excluded.is_empty() {
+            procbars.println(format!("Left {} out of {}: DQ'd out of every set",
+                excluded.join(", "), event.slug)).unwrap();
+        }
+    }
 
-    pr.calculate_scoring(constants::CalculationMethods::UnweightedPoints, &unweighted_score_bar);
+    for slug in missing_tournaments {
+        if !found_tournaments.contains(slug) && !failed_events.iter().any(|f| f == slug) {
+            procbars.println(format!("Skipping event {}: it isn't in {} yet, run without --offline to fetch it",
+                slug, settings.db.display())).unwrap();
+            failed_events.push(slug.to_string());
+        }
+    }
+    rankings
+}
 
-    unweighted_score_bar.finish();
+/// Runs field strength, the qualification check and every ranking method over
+/// each game's rankings.
+fn score_rankings(rankings: Vec<(Option<GameConfig>, PowerRankings)>, settings: &RankSettings,
+        procbars: &MultiProgress) -> Vec<RankingResults> {
+    let mut results: Vec<RankingResults> = vec![];
+    for (game, mut pr) in rankings {
+        if let Some(strength) = pr.scoring().field_strength.clone() {
+            match &strength.mode {
+                FieldStrengthMode::Prior { ranking } => {
+                    let path = settings.config.parent().unwrap_or(Path::new(".")).join(ranking);
+                    let ranking: Vec<String> = match read_lines(&path) {
+                        Ok(lines) => lines.map_while(Result::ok)
+                            .map(|l| l.trim().to_string())
+                            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+                            .collect(),
+                        Err(e) => {
+                            println!("Error reading prior ranking {}: {}", path.display(), e);
+                            exit(1);
+                        }
+                    };
+                    pr.apply_prior_field_strength(&ranking, strength.floor);
+                },
+                FieldStrengthMode::Iterative => pr.apply_iterative_field_strength(strength.floor)
+            }
+        }
 
-    pr.export_ordered(&mut outfile, prcalc::PROrder::HiFirst, 12, |placement, p, f| {
-        writeln!(f, "{:>3}  {:30}  AVG = {:5.4}", placement, p.get_name(), p.get_score()).unwrap();
-        // writeln!(f, "{:?}", p).unwrap();
-    });
+        /* ---- SECTION 0: QUALIFICATION CHECK ---- */
 
-    writeln!(outfile, "").unwrap();
-    writeln!(outfile, "").unwrap();
+        let qualified_player_bar = procbars.add(indicatif::ProgressBar::new(1));
+        qualified_player_bar.set_style(ProgressStyle::with_template(
+            "Checking who qualified   {pos:>3}/{len:3} {bar:>30.cyan}"
+        ).unwrap());
 
-    /* ---- SECTION 6: OVERALL PR ORDERING ---- */
+        pr.get_only_qualified_players(&qualified_player_bar);
+        qualified_player_bar.finish();
 
-    writeln!(outfile, "Overall PR Ordering").unwrap();
-    writeln!(outfile, "===================").unwrap();
+        /* ---- SECTIONS 1-8: EVERY RANKING METHOD ---- */
 
-    let pr_placement_bar = procbars.add(indicatif::ProgressBar::new(1));
-    pr_placement_bar.set_style(ProgressStyle::with_template(
-        "Scoring: PR Placement   {pos:>3}/{len:3} {bar:>30.cyan}"
-    ).unwrap());
+        let mut game_results = RankingResults::new(settings.season.map(|s| s.name.clone()),
+            game.as_ref().map(|g| g.name.clone()), &pr);
+        for spec in &export::METHODS {
+            let score_bar = procbars.add(indicatif::ProgressBar::new(1));
+            score_bar.set_style(ProgressStyle::with_template(
+                &format!("Scoring: {}   {{pos:>3}}/{{len:3}} {{bar:>30.cyan}}", spec.progress_label)
+            ).unwrap());
 
-    pr.calculate_scoring(constants::CalculationMethods::OverallPRPlacement, &pr_placement_bar);
+            pr.calculate_scoring(spec.method, &score_bar);
 
-    pr_placement_bar.finish();
+            score_bar.finish();
 
-    pr.export_ordered(&mut outfile, prcalc::PROrder::LoFirst, 20, |placement, p, f| {
-        writeln!(f, "{:>3}  {:30}  AVG = {:5.4}", placement, p.get_name(), p.get_score()).unwrap();
-        // writeln!(f, "{:?}", p).unwrap();
-    });
+            let ranked = pr.rank_players(spec.order);
+            game_results.add_method(spec, &pr, &ranked);
+        }
+        results.push(game_results);
+    }
+    results
+}
 
-    writeln!(outfile, "").unwrap();
-    writeln!(outfile, "").unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Ranks tests/fixtures/pr.toml's season from canned start.gg and
+    /// Challonge responses, through the database, into a results file.
+    #[tokio::test]
+    async fn ranks_a_season_from_fixtures() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let out = std::env::temp_dir().join(format!("rpipr-e2e-{}", std::process::id()));
+        std::fs::create_dir_all(&out).unwrap();
+        let (db, output) = (out.join("pr.db"), out.join("output.json"));
+        let _ = std::fs::remove_file(&db);
+
+        let config = fixtures.join("pr.toml");
+        let cli = Cli::parse_from([
+            "pr_solver", "rank", "--season", "e2e",
+            "--config", config.to_str().unwrap(),
+            "--fixtures", fixtures.to_str().unwrap(),
+            "--db", db.to_str().unwrap(),
+            "--format", "json",
+            "-o", output.to_str().unwrap()
+        ]);
+        match cli.command {
+            Some(Command::Rank(args)) => rank(args).await,
+            _ => unreachable!()
+        }
+        let results = export::load_results(&output, None).unwrap();
+        std::fs::remove_dir_all(&out).unwrap();
+
+        // a start.gg event, one picked from its tournament, one with sets (and a
+        // no-show) and a Challonge bracket
+        let slugs: Vec<&str> = results.events.iter().map(|e| e.slug.as_str()).collect();
+        assert_eq!(slugs, [
+            "tournament/spring-1/event/singles",
+            "tournament/spring-2/event/ultimate-singles",
+            "tournament/spring-3/event/singles",
+            "challonge/club-spring4"
+        ]);
+        assert_eq!(results.events[2].tier_multiplier, 1.25);
+        assert_eq!(results.events[2].excluded, ["Foxtrot"]);
+
+        // Delta became Delt4 halfway through, and is ranked once under the new tag
+        let overall = results.methods.iter().find(|m| m.key == "overall").unwrap();
+        let ranking: Vec<(&str, u32)> = overall.entries.iter()
+            .map(|e| (e.player.as_str(), e.events_entered))
+            .collect();
+        assert_eq!(ranking, [("Alpha", 4), ("Bravo", 4), ("Delt4", 4), ("Charlie", 3), ("Echo", 3)]);
+
+        let average = results.methods.iter().find(|m| m.key == "average_placement").unwrap();
+        let delt4 = average.entries.iter().find(|e| e.player == "Delt4").unwrap();
+        let placements: Vec<u32> = delt4.events.iter().map(|e| e.placement).collect();
+        assert_eq!(placements, [4, 3, 2, 4]);
+    }
 }
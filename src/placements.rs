@@ -0,0 +1,127 @@
+// Double elimination placement buckets: 1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, ...
+//
+// Everyone knocked out in the same losers round ties, so 5th covers 5-6th,
+// 7th covers 7-8th, 9th covers 9-12th and so on.
+
+/// The first placement of every bucket up to and including `max`.
+pub fn double_elim_placements(max: u32) -> Vec<u32> {
+    let mut placements: Vec<u32> = (1..=4.min(max)).collect();
+    let mut next: u32 = 5;
+    let mut step: u32 = 2;
+    let mut steps_at_size = 0;
+    while next <= max {
+        placements.push(next);
+        next = match next.checked_add(step) {
+            Some(n) => n,
+            None => break
+        };
+        steps_at_size += 1;
+        if steps_at_size == 2 {
+            step = step.saturating_mul(2);
+            steps_at_size = 0;
+        }
+    }
+    placements
+}
+
+/// Snaps a placement to the start of its bucket, for brackets that report
+/// placements that aren't bucket-aligned.
+pub fn bucket_start(placement: u32) -> u32 {
+    *double_elim_placements(placement.max(1)).last().unwrap()
+}
+
+/// How many buckets down from 1st a placement is.
+pub fn bucket_index(placement: u32) -> u32 {
+    double_elim_placements(placement.max(1)).len() as u32 - 1
+}
+
+/// The start of the bucket with index `index`, if it starts within `u32`.
+pub fn bucket_at(index: u32) -> Option<u32> {
+    let mut max: u32 = 1;
+    loop {
+        let placements = double_elim_placements(max);
+        if let Some(p) = placements.get(index as usize) {
+            return Some(*p);
+        }
+        if max == u32::MAX {
+            return None;
+        }
+        max = max.saturating_mul(2);
+    }
+}
+
+/// The last placement sharing `placement`'s bucket, capped at the field size.
+pub fn bucket_end(placement: u32, num_entrants: u32) -> u32 {
+    let last = bucket_at(bucket_index(placement) + 1).map_or(u32::MAX, |next| next - 1);
+    last.min(num_entrants.max(1))
+}
+
+/// A placement recounted with only `ahead` of the entrants before it left:
+/// one more than those, snapped to its bucket so ties stay tied.
+pub fn recount(placement: u32, ahead: u32) -> u32 {
+    bucket_start(ahead + 1).min(placement)
+}
+
+/// The fraction of the field a placement beat outright: 1.0 for winning,
+/// 0.0 for sharing last place.
+pub fn percentile(placement: u32, num_entrants: u32) -> f64 {
+    if num_entrants <= 1 {
+        return 1.0;
+    }
+    let beaten = num_entrants.saturating_sub(bucket_end(bucket_start(placement), num_entrants));
+    beaten as f64 / (num_entrants - 1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_at_odd_sizes() {
+        assert_eq!(double_elim_placements(6), vec![1, 2, 3, 4, 5]);
+        assert_eq!(double_elim_placements(12), vec![1, 2, 3, 4, 5, 7, 9]);
+        assert_eq!(double_elim_placements(40), vec![1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33]);
+
+        assert_eq!([6, 8, 12, 13, 24, 25].map(bucket_start), [5, 7, 9, 13, 17, 25]);
+        assert_eq!([1, 4, 5, 6, 7, 12, 13].map(bucket_index), [0, 3, 4, 4, 5, 6, 7]);
+        assert_eq!([0, 4, 5, 7].map(bucket_at), [Some(1), Some(5), Some(7), Some(13)]);
+
+        // the last bucket is cut short by the field size
+        assert_eq!(bucket_end(5, 6), 6);
+        assert_eq!(bucket_end(9, 10), 10);
+        assert_eq!(bucket_end(9, 20), 12);
+    }
+
+    #[test]
+    fn huge_placements_stop_at_the_last_bucket() {
+        let last = *double_elim_placements(u32::MAX).last().unwrap();
+        assert_eq!(bucket_start(u32::MAX), last);
+        assert_eq!(bucket_at(bucket_index(u32::MAX) + 1), None);
+        assert_eq!(bucket_end(3_000_000_000, u32::MAX), 3_221_225_472);
+        assert_eq!(bucket_end(u32::MAX, u32::MAX), u32::MAX);
+        assert_eq!(percentile(3_000_000_000, 32), 0.0);
+    }
+
+    #[test]
+    fn percentiles() {
+        assert_eq!(percentile(1, 10), 1.0);
+        assert_eq!(percentile(5, 10), 4.0 / 9.0);
+        assert_eq!(percentile(6, 10), 4.0 / 9.0);
+        assert_eq!(percentile(9, 10), 0.0);
+        assert_eq!(percentile(1, 1), 1.0);
+    }
+
+    #[test]
+    fn recounted_without_ineligible_players() {
+        // 1st and one of the 5ths are ineligible
+        let eligible = [2, 3, 4, 5, 7, 7];
+        let recounted = eligible.map(|p| recount(p, eligible.iter().filter(|q| **q < p).count() as u32));
+        assert_eq!(recounted, [1, 2, 3, 4, 5, 5]);
+
+        // never worse than the bracket's placement
+        assert_eq!(recount(3, 3), 3);
+        // and snapped to a bucket start
+        assert_eq!(recount(9, 5), 5);
+        assert_eq!(recount(9, 6), 7);
+    }
+}
@@ -0,0 +1,126 @@
+// `player <tag>`: why one player is ranked where they are.
+//
+// Reads a `--format json` results file rather than refetching anything, and
+// prints the player's events with their points under every method, the result
+// each method dropped, their rank in each method and how those ranks average
+// into the overall PR.
+
+use crate::export::{self, METHODS, RankEntry, RankingResults};
+use std::path::PathBuf;
+
+#[derive(clap::Args, Debug)]
+pub struct PlayerArgs {
+    /// The player's tag, as it appears in the results (case doesn't matter)
+    tag: String,
+
+    /// Results written by `--format json`
+    #[arg(long, default_value = "output.json")]
+    results: PathBuf,
+
+    /// Which game's rankings to read, when the results rank several
+    #[arg(long)]
+    game: Option<String>
+}
+
+pub fn player_report(args: PlayerArgs) -> Result<(), String> {
+    let results = export::load_results(&args.results, args.game.as_deref())?;
+    print!("{}", describe_player(&results, &args.tag)?);
+    Ok(())
+}
+
+/// Every method's entry for the player, in method order, or why they
+/// aren't ranked. Never empty when it's `Ok`.
+pub fn find_entries<'a>(results: &'a RankingResults, tag: &str) -> Result<Vec<(&'a str, &'a RankEntry)>, String> {
+    let entries: Vec<(&str, &RankEntry)> = results.methods.iter()
+        .filter_map(|m| m.entries.iter()
+            .find(|e| e.player.eq_ignore_ascii_case(tag))
+            .map(|e| (m.key.as_str(), e)))
+        .collect();
+    if !entries.is_empty() {
+        Ok(entries)
+    } else if results.ineligible.iter().any(|p| p.eq_ignore_ascii_case(tag)) {
+        Err(format!("\"{}\" isn't eligible for these rankings, though their placements still stand.", tag))
+    } else {
+        Err(format!("\"{}\" isn't in the rankings. Only players with at least {} events are ranked.",
+            tag, results.methodology.minimum_events))
+    }
+}
+
+/// A method's title in `results`, by key.
+pub fn method_title<'a>(results: &'a RankingResults, key: &'a str) -> &'a str {
+    results.methods.iter().find(|m| m.key == key).map(|m| m.title.as_str()).unwrap_or(key)
+}
+
+fn short_name(key: &str) -> &str {
+    METHODS.iter().find(|m| m.key == key).map(|m| m.short).unwrap_or(key)
+}
+
+fn describe_player(results: &RankingResults, tag: &str) -> Result<String, String> {
+    let entries = find_entries(results, tag)?;
+    let first = entries[0].1;
+
+    let mut out = String::new();
+    out += &format!("{}\n{}\n", first.player, "=".repeat(first.player.chars().count()));
+    let spr = first.seed_performance.map(|s| format!("{:+.2}", s)).unwrap_or_else(|| "n/a".to_string());
+    out += &format!("{} events entered, {} seeded, average seed performance {}\n\n",
+        first.events_entered, first.seeded_events, spr);
+
+    // one column per method that scores events individually
+    let point_methods: Vec<(&str, &RankEntry)> = entries.iter()
+        .filter(|(_, e)| e.events.iter().any(|ev| ev.points.is_some()))
+        .copied()
+        .collect();
+
+    // placements moved up past ineligible players show the bracket's too
+    let adjusted = first.events.iter().any(|e| e.raw_placement.is_some());
+    out += &format!("{:30}  {:10}  {:>8}  {:>5}", "Event", "Date", "Entrants", "Place");
+    if adjusted {
+        out += &format!("  {:>7}", "Bracket");
+    }
+    out += &format!("  {:>4}", "Seed");
+    for (key, _) in &point_methods {
+        out += &format!("  {:>11}", short_name(key));
+    }
+    out += "\n";
+    for event in &first.events {
+        let summary = results.events.get(event.event_id as usize);
+        let date = summary.and_then(|s| s.date).map(|d| d.to_string()).unwrap_or_else(|| "?".to_string());
+        let entrants = summary.map(|s| s.entrants.to_string()).unwrap_or_else(|| "?".to_string());
+        let seed = event.seed.map(|s| s.to_string()).unwrap_or_else(|| "-".to_string());
+        out += &format!("{:30}  {:10}  {:>8}  {:>5}", event.event, date, entrants, event.placement);
+        if adjusted {
+            out += &format!("  {:>7}", event.raw_placement.unwrap_or(event.placement));
+        }
+        out += &format!("  {:>4}", seed);
+        for (_, entry) in &point_methods {
+            let points = entry.events.iter().find(|e| e.event_id == event.event_id).and_then(|e| e.points);
+            let dropped = if entry.dropped_event == Some(event.event_id) { "*" } else { " " };
+            out += &match points {
+                Some(p) => format!("  {:>10.2}{}", p, dropped),
+                None => format!("  {:>11}", "-")
+            };
+        }
+        out += "\n";
+    }
+    out += "* dropped as the worst result under that method\n\n";
+
+    out += &format!("{:55}  {:>4}  {:>10}  Dropped\n", "Method", "Rank", "Score");
+    for (key, entry) in &entries {
+        let title = method_title(results, key);
+        let dropped = entry.dropped_event.map(|id| results.event_name(id)).unwrap_or("-");
+        out += &format!("{:55}  {:>4}  {:>10.4}  {}\n", title, entry.rank, entry.score, dropped);
+    }
+
+    // the overall PR averages the ranks from every method run before it
+    let overall = METHODS.iter().position(|m| m.key == "overall").unwrap();
+    let categories: Vec<u32> = METHODS[..overall].iter()
+        .filter_map(|m| entries.iter().find(|(key, _)| *key == m.key).map(|(_, e)| e.rank))
+        .collect();
+    if let Some((_, entry)) = entries.iter().find(|(key, _)| *key == "overall") {
+        let ranks: Vec<String> = categories.iter().map(|r| r.to_string()).collect();
+        out += &format!("\nOverall PR: mean of category ranks [{}] = {:.4}, placing #{}\n",
+            ranks.join(", "), entry.score, entry.rank);
+    }
+    Ok(out)
+}
+
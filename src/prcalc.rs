@@ -1,35 +1,189 @@
 use std::collections::{HashMap, HashSet};
+use chrono::NaiveDate;
+use crate::config::{QualificationConfig, ScoringConfig, TeamMode, Tier};
 use crate::constants::*;
+use crate::eligibility::Eligibility;
+use crate::placements;
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct TournamentDetails {
     pub tournament_name: String,
-    pub tournament_entrants: u32
+    pub slug: String,
+    pub tournament_entrants: u32,
+    pub start_date: Option<NaiveDate>,
+    /// Strength-adjusted entrant count, when field strength weighting is on.
+    pub field_strength: Option<f64>,
+    pub tier: Option<Tier>,
+    /// Manual multiplier, on top of the tier's.
+    pub weight: f64,
+    /// Entrants left out for being DQ'd out of every set.
+    pub excluded: Vec<String>
 }
 
+/// One event's results as fetched from a bracket site, before they're added
+/// to a `PowerRankings`.
+#[derive(Debug, Clone)]
+pub struct EventData {
+    pub slug: String,
+    pub name: String,
+    pub num_entrants: u32,
+    pub start_date: Option<NaiveDate>,
+    /// The site's videogame ID, when it says.
+    pub videogame: Option<u32>,
+    pub standings: Vec<EventStanding>,
+    pub sets: Vec<EventSet>
+}
+
+impl EventData {
+    /// The standings grouped by entrant, so teammates come together.
+    pub fn entrants(&self) -> Vec<Vec<&EventStanding>> {
+        let mut entrants: Vec<Vec<&EventStanding>> = vec![];
+        for standing in &self.standings {
+            let teammates = standing.team.as_ref()
+                .and_then(|team| entrants.iter_mut().find(|e| e[0].team.as_ref() == Some(team)));
+            match teammates {
+                Some(entrant) => entrant.push(standing),
+                None => entrants.push(vec![standing])
+            }
+        }
+        entrants
+    }
+
+    /// Everyone on an entrant that played at least one set and lost all of
+    /// them by DQ (a negative score), i.e. never showed up.
+    pub fn dq_only_entrants(&self) -> Vec<String> {
+        self.entrants().into_iter()
+            .filter(|members| {
+                let on_entrant = |name: &String| members.iter().any(|m| &m.name == name);
+                let mut played = self.sets.iter().filter(|set| on_entrant(&set.winner) || on_entrant(&set.loser)).peekable();
+                played.peek().is_some() && played.all(|set| on_entrant(&set.loser) && set.loser_score.is_some_and(|l| l < 0))
+            })
+            .flatten()
+            .map(|s| s.name.clone())
+            .collect()
+    }
+}
+
+/// A team's name when it's ranked as a unit: its members' tags in order, so
+/// the same pair is one unit whatever they called their team.
+pub fn team_name(members: &[&EventStanding]) -> String {
+    let mut tags: Vec<&str> = members.iter().map(|m| m.name.as_str()).collect();
+    tags.sort_by_key(|t| t.to_lowercase());
+    tags.join(" / ")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventStanding {
+    pub name: String,
+    /// The bracket site's ID for the player, which survives tag changes.
+    pub player_id: Option<String>,
+    pub placement: u32,
+    pub seed: Option<u32>,
+    /// Shared by everyone on the same doubles or team entrant; `None` for
+    /// someone entered alone.
+    pub team: Option<String>,
+    pub profile: PlayerProfile
+}
+
+/// What a bracket site says about who a player is with and where they're
+/// from, for eligibility.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlayerProfile {
+    /// The sponsor or school tag shown before their name.
+    pub prefix: Option<String>,
+    pub city: Option<String>,
+    pub state: Option<String>,
+    pub country: Option<String>
+}
+
+/// One set played at an event. Scores are `None` when the site only
+/// recorded who won.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventSet {
+    pub round: String,
+    pub winner: String,
+    pub winner_id: Option<String>,
+    pub loser: String,
+    pub loser_id: Option<String>,
+    pub winner_score: Option<i32>,
+    pub loser_score: Option<i32>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PROrder {
     LoFirst,
     HiFirst
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PowerRankings {
     tournaments: Vec<TournamentDetails>,
-    players: HashSet<Player>
+    players: HashSet<Player>,
+    scoring: ScoringConfig,
+    qualification: QualificationConfig,
+    eligibility: Option<Eligibility>,
+    /// Players who keep their placements in events but aren't ranked.
+    ineligible: HashSet<String>
 }
 
 impl PowerRankings {
-    pub fn new() -> Self {
+    pub fn with_config(scoring: ScoringConfig, qualification: QualificationConfig) -> Self {
         PowerRankings {
             tournaments: vec![],
-            players: HashSet::new()
+            players: HashSet::new(),
+            scoring,
+            qualification,
+            eligibility: None,
+            ineligible: HashSet::new()
         }
     }
 
+    /// Limits the rankings to players `eligibility` allows. Set it before
+    /// adding events.
+    pub fn set_eligibility(&mut self, eligibility: Eligibility) {
+        self.eligibility = Some(eligibility);
+    }
+
+    pub fn eligibility(&self) -> Option<&Eligibility> {
+        self.eligibility.as_ref()
+    }
+
+    /// Everyone left out of the rankings for not being eligible, by name.
+    pub fn ineligible_players(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.ineligible.iter().cloned().collect();
+        names.sort_by_key(|n| n.to_lowercase());
+        names
+    }
+
+    pub fn scoring(&self) -> &ScoringConfig {
+        &self.scoring
+    }
+
+    pub fn qualification(&self) -> &QualificationConfig {
+        &self.qualification
+    }
+
+    /// For trying other rules on an unscored copy. Only what's read while
+    /// scoring (points, drop-worst, decay and so on) changes once events are
+    /// added; DQ, team and eligibility handling were settled by `add_event`.
+    pub fn scoring_mut(&mut self) -> &mut ScoringConfig {
+        &mut self.scoring
+    }
+
+    pub fn qualification_mut(&mut self) -> &mut QualificationConfig {
+        &mut self.qualification
+    }
+
     pub fn add_tournament(&mut self, name: impl Into<String>, num_players: u32) -> u32 {
         let details = TournamentDetails {
             tournament_name: name.into(),
-            tournament_entrants: num_players
+            slug: String::new(),
+            tournament_entrants: num_players,
+            start_date: None,
+            field_strength: None,
+            tier: None,
+            weight: 1.0,
+            excluded: vec![]
         };
         let new_tournament_id = self.tournaments.len() as u32;
 
@@ -37,13 +191,81 @@ impl PowerRankings {
         new_tournament_id
     }
 
+    pub fn tournaments(&self) -> &[TournamentDetails] {
+        &self.tournaments
+    }
+
+    pub fn tournament_mut(&mut self, tournament_id: u32) -> Option<&mut TournamentDetails> {
+        self.tournaments.get_mut(tournament_id as usize)
+    }
+
+    /// Adds a fetched event and everyone's placement in it, returning the new
+    /// tournament's ID.
+    pub fn add_event(&mut self, event: &EventData, tier: Option<Tier>, weight: f64) -> u32 {
+        let excluded = if self.scoring.exclude_dqs { event.dq_only_entrants() } else { vec![] };
+        let entrants: Vec<Vec<&EventStanding>> = event.entrants().into_iter()
+            .filter(|members| !excluded.contains(&members[0].name))
+            .collect();
+        let num_entrants = if self.scoring.recount_entrants {
+            event.num_entrants.saturating_sub((event.entrants().len() - entrants.len()) as u32)
+        } else {
+            event.num_entrants
+        };
+
+        let tournament_id = self.add_tournament(event.name.clone(), num_entrants);
+        let details = self.tournament_mut(tournament_id).unwrap();
+        details.slug = event.slug.clone();
+        details.start_date = event.start_date;
+        details.tier = tier;
+        details.weight = weight;
+        details.excluded = excluded;
+
+        let allowed = |m: &EventStanding| self.eligibility.as_ref().is_none_or(|e| e.allows(m));
+        // who each entrant ranks as, and whether they're eligible; a unit is
+        // only as eligible as its least eligible member
+        let ranked: Vec<(Vec<(String, bool)>, &EventStanding)> = entrants.iter().map(|members| {
+            let names = match self.scoring.teams {
+                TeamMode::Individuals => members.iter().map(|m| (m.name.clone(), allowed(m))).collect(),
+                TeamMode::Units => vec![(team_name(members), members.iter().all(|m| allowed(m)))]
+            };
+            (names, members[0])
+        }).collect();
+        let eligible_placements: Vec<u32> = ranked.iter()
+            .filter(|(names, _)| names.iter().any(|(_, eligible)| *eligible))
+            .map(|(_, standing)| standing.placement)
+            .collect();
+
+        for (names, standing) in ranked {
+            let adjusted = if self.scoring.adjust_placements && self.eligibility.is_some() {
+                let ahead = eligible_placements.iter().filter(|p| **p < standing.placement).count() as u32;
+                placements::recount(standing.placement, ahead)
+            } else {
+                standing.placement
+            };
+            for (name, eligible) in names {
+                if !eligible {
+                    self.ineligible.insert(name.clone());
+                }
+                let placement = if eligible { adjusted } else { standing.placement };
+                self.add_player(name.clone());
+                self.add_placement_to_player(name.clone(), tournament_id, placement, standing.seed).unwrap();
+                if placement != standing.placement {
+                    let mut player = self.players.get(&Player::new(name)).unwrap().clone();
+                    player.raw_placements.insert(tournament_id, standing.placement);
+                    self.players.replace(player);
+                }
+            }
+        }
+        tournament_id
+    }
+
     pub fn add_player(&mut self, name: impl Into<String>) {
         let player = Player::new(name);
         self.players.insert(player);
     }
 
     pub fn add_placement_to_player(&mut self, player_name: impl Into<String>,
-            tournament_id: u32, placement: u32) -> Result<(), String> {
+            tournament_id: u32, placement: u32, seed: Option<u32>) -> Result<(), String> {
         
         let player_finder = Player::new(player_name);
         let player_ref = match self.players.get(&player_finder) {
@@ -54,7 +276,7 @@ impl PowerRankings {
         };
 
         let mut edited_player = player_ref.clone();
-        edited_player.add_player_to_tournament(tournament_id, placement);
+        edited_player.add_player_to_tournament(tournament_id, placement, seed);
         self.players.replace(edited_player);
 
         Ok(())
@@ -64,7 +286,7 @@ impl PowerRankings {
         bar.set_length(self.players.len() as u64);
         let mut tmp_hash_set: HashSet<Player> = HashSet::new();
         for p in &self.players {
-            if p.qualifies_for_pr() {
+            if p.qualifies_for_pr(self.qualification.minimum_events) && !self.ineligible.contains(&p.name) {
                 tmp_hash_set.insert(p.clone());
             }
             bar.inc(1);
@@ -78,48 +300,64 @@ impl PowerRankings {
         match score_type {
             CalculationMethods::AveragePlacement => {
                 let mut tmp_vec: Vec<Player> = self.players.drain().collect();
-                for mut p in tmp_vec.iter_mut() {
-                    self.average_placement_score_fn(&mut p);
+                for p in tmp_vec.iter_mut() {
+                    self.average_placement_score_fn(p);
                     bar.inc(1);
                 }
                 self.players = tmp_vec.drain(0..).collect();
             },
             CalculationMethods::WeightedPoints => {
                 let mut tmp_vec: Vec<Player> = self.players.drain().collect();
-                for mut p in tmp_vec.iter_mut() {
-                    self.weighted_points_score_fn(&mut p);
+                for p in tmp_vec.iter_mut() {
+                    self.weighted_points_score_fn(p);
                     bar.inc(1);
                 }
                 self.players = tmp_vec.drain(0..).collect();
             },
             CalculationMethods::MedianPoints => {
                 let mut tmp_vec: Vec<Player> = self.players.drain().collect();
-                for mut p in tmp_vec.iter_mut() {
-                    self.median_points_score_fn(&mut p);
+                for p in tmp_vec.iter_mut() {
+                    self.median_points_score_fn(p);
                     bar.inc(1);
                 }
                 self.players = tmp_vec.drain(0..).collect();
             },
             CalculationMethods::MeanPoints => {
                 let mut tmp_vec: Vec<Player> = self.players.drain().collect();
-                for mut p in tmp_vec.iter_mut() {
-                    self.mean_points_score_fn(&mut p);
+                for p in tmp_vec.iter_mut() {
+                    self.mean_points_score_fn(p);
                     bar.inc(1);
                 }
                 self.players = tmp_vec.drain(0..).collect();
             },
             CalculationMethods::UnweightedPoints => {
                 let mut tmp_vec: Vec<Player> = self.players.drain().collect();
-                for mut p in tmp_vec.iter_mut() {
-                    self.unweighted_points_score_fn(&mut p);
+                for p in tmp_vec.iter_mut() {
+                    self.unweighted_points_score_fn(p);
+                    bar.inc(1);
+                }
+                self.players = tmp_vec.drain(0..).collect();
+            },
+            CalculationMethods::PercentilePoints => {
+                let mut tmp_vec: Vec<Player> = self.players.drain().collect();
+                for p in tmp_vec.iter_mut() {
+                    self.percentile_points_score_fn(p);
+                    bar.inc(1);
+                }
+                self.players = tmp_vec.drain(0..).collect();
+            },
+            CalculationMethods::SeedPerformance => {
+                let mut tmp_vec: Vec<Player> = self.players.drain().collect();
+                for p in tmp_vec.iter_mut() {
+                    self.seed_performance_score_fn(p);
                     bar.inc(1);
                 }
                 self.players = tmp_vec.drain(0..).collect();
             },
             CalculationMethods::OverallPRPlacement => {
                 let mut tmp_vec: Vec<Player> = self.players.drain().collect();
-                for mut p in tmp_vec.iter_mut() {
-                    self.pr_category_score_fn(&mut p);
+                for p in tmp_vec.iter_mut() {
+                    self.pr_category_score_fn(p);
                     bar.inc(1);
                 }
                 self.players = tmp_vec.drain(0..).collect();
@@ -127,6 +365,126 @@ impl PowerRankings {
         }
     }
 
+    /// The entrant count the weighted methods scale points by: the real one,
+    /// or the strength-adjusted one if field strength has been computed.
+    pub fn event_size(&self, tournament: &TournamentDetails) -> f64 {
+        tournament.field_strength.unwrap_or(tournament.tournament_entrants as f64)
+    }
+
+    /// Weighs events by a previous ranking: players are worth 1.0 at the top
+    /// of `ranking` down to `floor` for anyone not in it.
+    pub fn apply_prior_field_strength(&mut self, ranking: &[String], floor: f64) {
+        let strengths: HashMap<&str, f64> = ranking.iter().enumerate()
+            .map(|(idx, name)| (name.as_str(), 1.0 - (1.0 - floor) * idx as f64 / ranking.len() as f64))
+            .collect();
+        let per_player: HashMap<String, f64> = self.players.iter()
+            .map(|p| (p.name.clone(), *strengths.get(p.name.as_str()).unwrap_or(&floor)))
+            .collect();
+        let sizes = self.field_strength_sizes(&per_player, floor);
+        for (t, size) in self.tournaments.iter_mut().zip(sizes) {
+            t.field_strength = Some(size);
+        }
+    }
+
+    /// Weighs events by the current ranking itself: score everyone with
+    /// weighted points, turn scores into strengths, reweigh events by those,
+    /// and repeat until the event weights settle.
+    pub fn apply_iterative_field_strength(&mut self, floor: f64) {
+        for t in self.tournaments.iter_mut() {
+            t.field_strength = None;
+        }
+
+        for _ in 0..FIELD_STRENGTH_MAX_ITERATIONS {
+            let scores: HashMap<String, f64> = self.players.iter()
+                .map(|p| {
+                    let mut scored = p.clone();
+                    self.weighted_points_score_fn(&mut scored);
+                    (scored.name, scored.score)
+                })
+                .collect();
+            let best = scores.values().cloned().fold(0.0, f64::max);
+            let per_player: HashMap<String, f64> = scores.into_iter()
+                .map(|(name, score)| {
+                    let strength = if best > 0.0 { score / best } else { 1.0 };
+                    (name, strength.max(floor))
+                })
+                .collect();
+
+            let sizes = self.field_strength_sizes(&per_player, floor);
+            let mut largest_change: f64 = 0.0;
+            for (t, size) in self.tournaments.iter_mut().zip(sizes) {
+                let old = t.field_strength.unwrap_or(t.tournament_entrants as f64);
+                // damped, so that the fixed point doesn't oscillate
+                let new = (old + size) / 2.0;
+                largest_change = largest_change.max((new - old).abs() / old.max(1.0));
+                t.field_strength = Some(new);
+            }
+            if largest_change < FIELD_STRENGTH_TOLERANCE { break; }
+        }
+    }
+
+    /// Sums attendee strengths per event, counting anyone past the end of the
+    /// fetched standings at `floor`, then rescales so the season's total
+    /// matches its real total entrant count.
+    fn field_strength_sizes(&self, per_player: &HashMap<String, f64>, floor: f64) -> Vec<f64> {
+        let mut raw: Vec<f64> = vec![0.0; self.tournaments.len()];
+        let mut listed: Vec<u32> = vec![0; self.tournaments.len()];
+        for p in &self.players {
+            let strength = *per_player.get(&p.name).unwrap_or(&floor);
+            for tournament_id in p.placements.keys() {
+                raw[*tournament_id as usize] += strength;
+                listed[*tournament_id as usize] += 1;
+            }
+        }
+        for (idx, t) in self.tournaments.iter().enumerate() {
+            raw[idx] += floor * t.tournament_entrants.saturating_sub(listed[idx]) as f64;
+        }
+
+        let total_entrants: f64 = self.tournaments.iter().map(|t| t.tournament_entrants as f64).sum();
+        let total_raw: f64 = raw.iter().sum();
+        if total_raw <= 0.0 {
+            return self.tournaments.iter().map(|t| t.tournament_entrants as f64).collect();
+        }
+        raw.iter().map(|r| r * total_entrants / total_raw).collect()
+    }
+
+    /// The date event ages are measured from when decay is enabled: the
+    /// configured reference, or else the most recent event.
+    pub fn decay_reference_date(&self) -> Option<NaiveDate> {
+        let decay = self.scoring.decay.as_ref()?;
+        decay.reference.or_else(|| self.tournaments.iter().filter_map(|t| t.start_date).max())
+    }
+
+    pub fn decay_factor(&self, tournament: &TournamentDetails) -> f64 {
+        let decay = match &self.scoring.decay {
+            Some(d) => d,
+            None => return 1.0
+        };
+        match (self.decay_reference_date(), tournament.start_date) {
+            (Some(reference), Some(date)) => decay.curve.factor((reference - date).num_days() as f64),
+            _ => 1.0
+        }
+    }
+
+    /// How much a tournament's points count for: its tier multiplier times its
+    /// manual weight, scaled by time decay if that's enabled.
+    pub fn tournament_multiplier(&self, tournament: &TournamentDetails) -> f64 {
+        self.scoring.tier_multiplier(tournament.tier) * tournament.weight * self.decay_factor(tournament)
+    }
+
+    /// Drops the worst result once a player has entered more than the
+    /// qualification minimum, then averages over at least that many events.
+    fn drop_worst_and_average(&self, mut total: f64, worst: f64, num_entered: u32) -> f64 {
+        let minimum_events = self.qualification.minimum_events;
+        let mut counted = num_entered;
+        if self.scoring.drop_worst && num_entered > minimum_events {
+            total -= worst;
+            counted -= 1;
+        }
+
+        total / (counted.max(minimum_events).max(1) as f64)
+    }
+
     fn average_placement_score_fn(&mut self, player: &mut Player) {
         let mut total: f64 = 0.0;
         let worst_placement: u32 = player.get_worst_placement();
@@ -134,147 +492,141 @@ impl PowerRankings {
             total += *placement.1 as f64;
         }
 
-        if player.get_num_tournaments_entered() > 4 {
-            total -= worst_placement as f64;
-        }
-    
-        total = total / ((player.get_num_tournaments_entered() - 1) as f64).max(4.0);
-        player.score = total;
+        player.score = self.drop_worst_and_average(total, worst_placement as f64, player.get_num_tournaments_entered());
+    }
+
+    fn weighted_points_score_fn(&self, player: &mut Player) {
+        self.points_score_fn(CalculationMethods::WeightedPoints, player);
+    }
+
+    fn median_points_score_fn(&mut self, player: &mut Player) {
+        self.points_score_fn(CalculationMethods::MedianPoints, player);
+    }
+
+    fn mean_points_score_fn(&mut self, player: &mut Player) {
+        self.points_score_fn(CalculationMethods::MeanPoints, player);
+    }
+
+    fn unweighted_points_score_fn(&mut self, player: &mut Player) {
+        self.points_score_fn(CalculationMethods::UnweightedPoints, player);
     }
 
-    fn weighted_points_score_fn(&mut self, player: &mut Player) {
+    fn percentile_points_score_fn(&mut self, player: &mut Player) {
+        self.points_score_fn(CalculationMethods::PercentilePoints, player);
+    }
+
+    fn points_score_fn(&self, method: CalculationMethods, player: &mut Player) {
         let mut total: f64 = 0.0;
         let mut worst_score: f64 = 10000.0;
         for (tournament_id, placement) in &player.placements {
-            let val: f64 =
-                (self.tournaments.get(*tournament_id as usize).unwrap().tournament_entrants as f64
-                    / MINIMUM_ENTRANT_COUNT as f64) * point_values(*placement);
+            let val: f64 = self.event_points(method, *tournament_id, *placement).unwrap();
             if val < worst_score {
                 worst_score = val;
             }
             total += val;
         }
 
-        if player.get_num_tournaments_entered() > 4 {
-            total -= worst_score;
-        }
-    
-        total = total / ((player.get_num_tournaments_entered() - 1) as f64).max(4.0);
-        player.score = total;
+        player.score = self.drop_worst_and_average(total, worst_score, player.get_num_tournaments_entered());
     }
 
-    fn median_points_score_fn(&mut self, player: &mut Player) {
-        let mut tournament_entrants: Vec<u32> = self.tournaments.iter()
-            .map(|o| {o.tournament_entrants})
+    fn median_event_size(&self) -> f64 {
+        let mut tournament_entrants: Vec<f64> = self.tournaments.iter()
+            .map(|o| {self.event_size(o)})
             .collect();
-        tournament_entrants.sort();
-        let med_entrants: f64 = match tournament_entrants.len() % 2 {
+        tournament_entrants.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        match tournament_entrants.len() % 2 {
             0 => {
-                (*tournament_entrants.get(tournament_entrants.len() / 2).unwrap() as f64 +
-                *tournament_entrants.get(tournament_entrants.len() / 2 + 1).unwrap() as f64) / 2.0
+                (*tournament_entrants.get(tournament_entrants.len() / 2 - 1).unwrap() +
+                *tournament_entrants.get(tournament_entrants.len() / 2).unwrap()) / 2.0
             },
             1 => {
-                *tournament_entrants.get(tournament_entrants.len() / 2).unwrap() as f64
+                *tournament_entrants.get(tournament_entrants.len() / 2).unwrap()
             }
             _ => 0.0
-        };
-        // println!("median is {:5.4}", med_entrants);
-        drop(tournament_entrants);
-
-        let mut total: f64 = 0.0;
-        let mut worst_score: f64 = 10000.0;
-        for (tournament_id, placement) in &player.placements {
-            let val: f64 =
-                (self.tournaments.get(*tournament_id as usize).unwrap().tournament_entrants as f64
-                    / med_entrants) * point_values(*placement);
-            if val < worst_score {
-                worst_score = val;
-            }
-            total += val;
-        }
-
-        if player.get_num_tournaments_entered() > 4 {
-            total -= worst_score;
         }
-    
-        total = total / ((player.get_num_tournaments_entered() - 1) as f64).max(4.0);
-        player.score = total;
     }
 
-    fn mean_points_score_fn(&mut self, player: &mut Player) {
-        let tournament_entrants: Vec<u32> = self.tournaments.iter()
-            .map(|o| {o.tournament_entrants})
+    fn mean_event_size(&self) -> f64 {
+        let tournament_entrants: Vec<f64> = self.tournaments.iter()
+            .map(|o| {self.event_size(o)})
             .collect();
-            let mean: f64 = tournament_entrants.iter().sum::<u32>() as f64 / tournament_entrants.len() as f64;
-        // println!("mean is {:5.4}", mean);
-        drop(tournament_entrants);
-
-        let mut total: f64 = 0.0;
-        let mut worst_score: f64 = 10000.0;
-        for (tournament_id, placement) in &player.placements {
-            let val: f64 =
-                (self.tournaments.get(*tournament_id as usize).unwrap().tournament_entrants as f64
-                    / mean) * point_values(*placement);
-            if val < worst_score {
-                worst_score = val;
-            }
-            total += val;
-        }
+        tournament_entrants.iter().sum::<f64>() / tournament_entrants.len() as f64
+    }
 
-        if player.get_num_tournaments_entered() > 4 {
-            total -= worst_score;
+    /// What one result is worth under a points-based method (or, for average
+    /// placement, the placement itself). `None` for methods that don't score
+    /// events individually.
+    pub fn event_points(&self, method: CalculationMethods, tournament_id: u32, placement: u32) -> Option<f64> {
+        let tournament = self.tournaments.get(tournament_id as usize)?;
+        let points = self.scoring.points.points(placement);
+        let multiplier = self.tournament_multiplier(tournament);
+        match method {
+            CalculationMethods::AveragePlacement => Some(placement as f64),
+            CalculationMethods::WeightedPoints => Some(
+                (self.event_size(tournament) / self.scoring.minimum_entrants as f64) * points * multiplier),
+            CalculationMethods::MedianPoints => Some(
+                (self.event_size(tournament) / self.median_event_size()) * points * multiplier),
+            CalculationMethods::MeanPoints => Some(
+                (self.event_size(tournament) / self.mean_event_size()) * points * multiplier),
+            CalculationMethods::UnweightedPoints => Some(points * multiplier),
+            CalculationMethods::PercentilePoints => {
+                let percentile = placements::percentile(placement, tournament.tournament_entrants);
+                Some(self.scoring.percentile.points(percentile) * multiplier)
+            },
+            CalculationMethods::SeedPerformance | CalculationMethods::OverallPRPlacement => None
         }
-    
-        total = total / ((player.get_num_tournaments_entered() - 1) as f64).max(4.0);
-        player.score = total;
     }
 
-    fn unweighted_points_score_fn(&mut self, player: &mut Player) {
-        let mut total: f64 = 0.0;
-        let mut worst_score: f64 = 10000.0;
-        for (_, placement) in &player.placements {
-            let val: f64 = point_values(*placement);
-            if val < worst_score {
-                worst_score = val;
-            }
-            total += val;
+    /// The event a player's worst result under `method` was dropped from,
+    /// if the drop rule applied to them.
+    pub fn dropped_event(&self, method: CalculationMethods, player: &Player) -> Option<u32> {
+        if !self.scoring.drop_worst || player.get_num_tournaments_entered() <= self.qualification.minimum_events {
+            return None;
         }
-
-        if player.get_num_tournaments_entered() > 4 {
-            total -= worst_score;
+        let placements = player.get_placements();
+        match method {
+            CalculationMethods::AveragePlacement => placements.iter()
+                .max_by_key(|(t, placement)| (*placement, std::cmp::Reverse(*t)))
+                .map(|(t, _)| *t),
+            CalculationMethods::OverallPRPlacement | CalculationMethods::SeedPerformance => None,
+            _ => placements.iter()
+                .filter_map(|(t, placement)| self.event_points(method, *t, *placement).map(|pts| (*t, pts)))
+                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+                .map(|(t, _)| t)
         }
-    
-        total = total / ((player.get_num_tournaments_entered() - 1) as f64).max(4.0);
-        player.score = total;
+    }
+
+    // players without a seeded event sort last, below any real rating, and
+    // are left out of the section
+    fn seed_performance_score_fn(&mut self, player: &mut Player) {
+        player.score = player.average_seed_performance().unwrap_or(f64::NEG_INFINITY);
     }
 
     fn pr_category_score_fn(&mut self, player: &mut Player) {
-        let avg_placement: f64 = player.pr_category_placements.iter().sum::<u32>() as f64 / 5.0;
+        let categories = player.pr_category_placements.len().max(1);
+        let avg_placement: f64 = player.pr_category_placements.iter().sum::<u32>() as f64 / categories as f64;
         player.score = avg_placement;
     }
 
-    pub fn export_ordered(&mut self, outfile: &mut dyn std::io::Write,
-            order: PROrder,
-            number: u32,
-            callback: impl Fn(u32, &Player, &mut dyn std::io::Write) -> ()) {
+    /// Sorts players by their current score, records each one's place for the
+    /// overall ordering, and returns them best first.
+    pub fn rank_players(&mut self, order: PROrder) -> Vec<Player> {
         let mut tmp_vec: Vec<Player> = self.players.drain().collect();
+        // ties are broken by name so that reruns come out identical
+        let by_score = |a: &Player, b: &Player| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal);
         match order {
             PROrder::HiFirst => {
-                tmp_vec.sort_by(|p, p2| {p2.partial_cmp(p).unwrap()});
+                tmp_vec.sort_by(|p, p2| {by_score(p2, p).then_with(|| p.name.cmp(&p2.name))});
             },
             PROrder::LoFirst => {
-                tmp_vec.sort_by(|p, p2| {p.partial_cmp(p2).unwrap()});
+                tmp_vec.sort_by(|p, p2| {by_score(p, p2).then_with(|| p.name.cmp(&p2.name))});
             }
         }
-        let mut placement: u32 = 1;
-        for p in tmp_vec.iter_mut() {
+        for (placement, p) in (1..).zip(tmp_vec.iter_mut()) {
             p.pr_category_placements.push(placement);
-            if placement <= number {
-                callback(placement, &p, outfile);
-            }
-            placement += 1;
         }
-        self.players = tmp_vec.drain(0..).collect();
+        self.players = tmp_vec.iter().cloned().collect();
+        tmp_vec
     }
 }
 
@@ -282,6 +634,11 @@ impl PowerRankings {
 pub struct Player {
     name: String,
     placements: HashMap<u32, u32>,
+    /// The bracket's placement, where `placements` holds one adjusted for
+    /// ineligible players ahead.
+    raw_placements: HashMap<u32, u32>,
+    /// Initial seed per tournament, where the bracket site had one.
+    seeds: HashMap<u32, u32>,
     pub pr_category_placements: Vec<u32>,
     score: f64
 }
@@ -292,18 +649,59 @@ impl Player {
         Player {
             name: name.into(),
             placements: HashMap::new(),
+            raw_placements: HashMap::new(),
+            seeds: HashMap::new(),
             pr_category_placements: vec![],
             score: 0.0 // low score, so that this score will never win without edits
         }
     }
 
-    pub fn add_player_to_tournament(&mut self, tournament_id: u32, placement: u32) {
+    pub fn add_player_to_tournament(&mut self, tournament_id: u32, placement: u32, seed: Option<u32>) {
         self.placements.insert(tournament_id, placement);
+        if let Some(seed) = seed {
+            self.seeds.insert(tournament_id, seed);
+        }
+    }
+
+    /// Seed performance rating at one tournament: how many placement buckets
+    /// better (positive) or worse (negative) the player did than their seed.
+    pub fn seed_performance(&self, tournament_id: u32) -> Option<i32> {
+        let seed = *self.seeds.get(&tournament_id)?;
+        // seeds were set with everyone in the bracket, so compare like with like
+        let placement = self.get_raw_placement(tournament_id)?;
+        Some(placements::bucket_index(seed) as i32 - placements::bucket_index(placement) as i32)
+    }
+
+    /// Mean SPR over every seeded tournament the player entered.
+    pub fn average_seed_performance(&self) -> Option<f64> {
+        let sprs: Vec<i32> = self.placements.keys()
+            .filter_map(|id| self.seed_performance(*id))
+            .collect();
+        if sprs.is_empty() {
+            return None;
+        }
+        Some(sprs.iter().sum::<i32>() as f64 / sprs.len() as f64)
+    }
+
+    /// `(tournament_id, placement)` for every event entered, in event order.
+    pub fn get_placements(&self) -> Vec<(u32, u32)> {
+        let mut placements: Vec<(u32, u32)> = self.placements.iter().map(|(t, p)| (*t, *p)).collect();
+        placements.sort();
+        placements
+    }
+
+    /// The placement the bracket gave, before any adjustment.
+    pub fn get_raw_placement(&self, tournament_id: u32) -> Option<u32> {
+        self.raw_placements.get(&tournament_id).or(self.placements.get(&tournament_id)).copied()
+    }
+
+    pub fn get_seed(&self, tournament_id: u32) -> Option<u32> {
+        self.seeds.get(&tournament_id).copied()
     }
 
     pub fn get_worst_placement(&self) -> u32 {
         let mut lowest: u32 = 0;
-        for (_, placement) in &self.placements {
+        for placement in self.placements.values() {
             if *placement > lowest {
                 lowest = *placement;
             }
@@ -315,6 +713,10 @@ impl Player {
         self.placements.len() as u32
     }
 
+    pub fn get_num_seeded_tournaments(&self) -> u32 {
+        self.seeds.len() as u32
+    }
+
     pub fn get_name(&self) -> &str {
         &self.name
     }
@@ -323,8 +725,8 @@ impl Player {
         self.score
     }
 
-    pub fn qualifies_for_pr(&self) -> bool {
-        self.get_num_tournaments_entered() >= 4
+    pub fn qualifies_for_pr(&self, minimum_events: u32) -> bool {
+        self.get_num_tournaments_entered() >= minimum_events
     }
 }
 
@@ -332,10 +734,6 @@ impl PartialEq for Player {
     fn eq(&self, other: &Self) -> bool {
         self.name == other.name
     }
-
-    fn ne(&self, other: &Self) -> bool {
-        !self.eq(other)
-    }
 }
 
 impl PartialOrd for Player {
@@ -355,11 +753,9 @@ impl PartialOrd for Player {
         self.score < other.score
     }
 
+    // by score alone, like the comparisons above; `==` compares names
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        if self < other { return Some(std::cmp::Ordering::Less); }
-        if self > other { return Some(std::cmp::Ordering::Greater); }
-        if self <= other && self >= other { return Some(std::cmp::Ordering::Equal); }
-        None
+        self.score.partial_cmp(&other.score)
     }
 }
 
@@ -370,3 +766,309 @@ impl std::hash::Hash for Player {
         self.name.hash(state);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{DecayConfig, DecayCurve};
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    /// Rankings with one tournament per `(date, weight)`.
+    fn with_events(scoring: ScoringConfig, events: &[(&str, f64)]) -> PowerRankings {
+        let mut pr = PowerRankings::with_config(scoring, QualificationConfig::default());
+        for (idx, (start, weight)) in events.iter().enumerate() {
+            let id = pr.add_tournament(format!("event {}", idx), 32);
+            let details = pr.tournament_mut(id).unwrap();
+            details.start_date = Some(date(start));
+            details.weight = *weight;
+        }
+        pr
+    }
+
+    /// Places `players` at tournament `id` in the order given, 1st first.
+    fn place(pr: &mut PowerRankings, id: u32, players: &[&str]) {
+        for (idx, name) in players.iter().enumerate() {
+            pr.add_player(*name);
+            pr.add_placement_to_player(*name, id, idx as u32 + 1, None).unwrap();
+        }
+    }
+
+    fn sizes(pr: &PowerRankings) -> Vec<f64> {
+        pr.tournaments.iter().map(|t| pr.event_size(t)).collect()
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-4, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    fn multipliers(pr: &PowerRankings) -> Vec<f64> {
+        pr.tournaments.iter().map(|t| pr.tournament_multiplier(t)).collect()
+    }
+
+    #[test]
+    fn decay_is_measured_from_the_latest_event() {
+        let mut scoring = ScoringConfig {
+            decay: Some(DecayConfig { curve: DecayCurve::HalfLife { days: 30.0 }, reference: None }),
+            ..ScoringConfig::default()
+        };
+        let events = [("2026-01-01", 1.0), ("2026-01-31", 2.0)];
+        let pr = with_events(scoring.clone(), &events);
+        assert_eq!(pr.decay_reference_date(), Some(date("2026-01-31")));
+        assert_eq!(multipliers(&pr), vec![0.5, 2.0]);
+
+        scoring.decay.as_mut().unwrap().reference = Some(date("2026-03-02"));
+        assert_eq!(multipliers(&with_events(scoring, &events)), vec![0.25, 1.0]);
+
+        // without decay only the weight counts
+        assert_eq!(multipliers(&with_events(ScoringConfig::default(), &events)), vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn multiplier_is_tier_times_weight_times_decay() {
+        let scoring = ScoringConfig {
+            decay: Some(DecayConfig { curve: DecayCurve::HalfLife { days: 30.0 }, reference: None }),
+            ..ScoringConfig::default()
+        };
+        let mut pr = with_events(scoring, &[("2026-01-01", 2.0), ("2026-01-31", 1.0), ("2026-01-31", 1.0)]);
+        pr.tournament_mut(0).unwrap().tier = Some(Tier::A);
+        pr.tournament_mut(1).unwrap().tier = Some(Tier::C);
+        assert_eq!(multipliers(&pr), vec![TIER_A_MULTIPLIER * 2.0 * 0.5, TIER_C_MULTIPLIER, 1.0]);
+    }
+
+    #[test]
+    fn prior_field_strength() {
+        let mut pr = with_events(ScoringConfig::default(), &[("2026-01-01", 1.0), ("2026-01-08", 1.0)]);
+        place(&mut pr, 0, &["A", "B"]);
+        place(&mut pr, 1, &["C", "D"]);
+        // 30 more entrants at each event that the standings don't list
+        assert_eq!(sizes(&pr), vec![32.0, 32.0]);
+
+        pr.apply_prior_field_strength(&["A".to_string(), "B".to_string()], 0.1);
+        // A is worth 1.0, B 0.55 and everyone else 0.1, scaled to 64 entrants in all
+        let (strong, weak) = (1.0 + 0.55 + 3.0, 0.2 + 3.0);
+        let scale = 64.0 / (strong + weak);
+        assert_close(&sizes(&pr), &[strong * scale, weak * scale]);
+    }
+
+    #[test]
+    fn seed_performance_counts_buckets() {
+        let mut pr = with_events(ScoringConfig::default(), &[("2026-01-01", 1.0), ("2026-01-08", 1.0)]);
+        // seeded 9th (bucket 6), placed 3rd (bucket 2): four buckets up
+        pr.add_player("Upset");
+        pr.add_placement_to_player("Upset", 0, 3, Some(9)).unwrap();
+        // seeded 1st, placed 7th: five buckets down, then as seeded
+        pr.add_player("Favorite");
+        pr.add_placement_to_player("Favorite", 0, 7, Some(1)).unwrap();
+        pr.add_placement_to_player("Favorite", 1, 1, Some(1)).unwrap();
+        // seeded 5th and 6th share a bucket with 5th place
+        pr.add_player("Even");
+        pr.add_placement_to_player("Even", 1, 5, Some(6)).unwrap();
+        pr.add_player("Unseeded");
+        pr.add_placement_to_player("Unseeded", 1, 2, None).unwrap();
+
+        let player = |name: &str| pr.players.get(&Player::new(name)).unwrap().clone();
+        assert_eq!(player("Upset").seed_performance(0), Some(4));
+        assert_eq!(player("Favorite").seed_performance(0), Some(-5));
+        assert_eq!(player("Favorite").average_seed_performance(), Some(-2.5));
+        assert_eq!(player("Even").average_seed_performance(), Some(0.0));
+        assert_eq!(player("Unseeded").average_seed_performance(), None);
+        assert_eq!(player("Unseeded").get_num_seeded_tournaments(), 0);
+
+        pr.calculate_scoring(CalculationMethods::SeedPerformance, &indicatif::ProgressBar::hidden());
+        let order: Vec<String> = pr.rank_players(PROrder::HiFirst).iter().map(|p| p.get_name().to_string()).collect();
+        assert_eq!(order, ["Upset", "Even", "Favorite", "Unseeded"]);
+    }
+
+    /// Event sizes after one more undamped round of the iterative method.
+    fn next_sizes(pr: &PowerRankings, floor: f64) -> Vec<f64> {
+        let scores: Vec<(String, f64)> = pr.players.iter().map(|p| {
+            let mut scored = p.clone();
+            pr.weighted_points_score_fn(&mut scored);
+            (scored.name, scored.score)
+        }).collect();
+        let best = scores.iter().map(|s| s.1).fold(0.0, f64::max);
+        let per_player = scores.into_iter().map(|(name, score)| (name, (score / best).max(floor))).collect();
+        pr.field_strength_sizes(&per_player, floor)
+    }
+
+    #[test]
+    fn iterative_field_strength_settles() {
+        let mut pr = with_events(ScoringConfig::default(),
+            &[("2026-01-01", 1.0), ("2026-01-08", 1.0), ("2026-01-15", 1.0)]);
+        place(&mut pr, 0, &["A", "B", "C", "D"]);
+        place(&mut pr, 1, &["A", "B", "E", "F"]);
+        place(&mut pr, 2, &["E", "F", "G", "H"]);
+
+        pr.apply_iterative_field_strength(0.1);
+        let settled = sizes(&pr);
+        assert!((settled.iter().sum::<f64>() - 96.0).abs() < 1e-6);
+        assert!(settled[0] > settled[2] && settled[1] > settled[2]);
+        // a fixed point: scoring with the settled sizes gives them back
+        assert_close(&next_sizes(&pr, 0.1), &settled);
+
+        // and starting over gets there again
+        pr.apply_iterative_field_strength(0.1);
+        assert_close(&sizes(&pr), &settled);
+    }
+
+    #[test]
+    fn players_with_equal_scores_compare_equal() {
+        let mut a = Player::new("Alpha");
+        let mut b = Player::new("Bravo");
+        a.score = 2.5;
+        b.score = 2.5;
+        assert_eq!(a.partial_cmp(&b), Some(std::cmp::Ordering::Equal));
+        b.score = 3.0;
+        assert_eq!(a.partial_cmp(&b), Some(std::cmp::Ordering::Less));
+    }
+
+    fn set(winner: &str, loser: &str, winner_score: Option<i32>, loser_score: Option<i32>) -> EventSet {
+        EventSet {
+            round: String::new(),
+            winner: winner.to_string(),
+            winner_id: None,
+            loser: loser.to_string(),
+            loser_id: None,
+            winner_score,
+            loser_score
+        }
+    }
+
+    /// A beats B; C is DQ'd by A but beats D; D is DQ'd out of both its sets;
+    /// E's only loss has no score; F never played.
+    fn event_with_dqs() -> EventData {
+        let standings = ["A", "B", "C", "D", "E", "F"].iter().enumerate()
+            .map(|(idx, name)| EventStanding {
+                name: name.to_string(),
+                player_id: None,
+                placement: idx as u32 + 1,
+                seed: None,
+                team: None,
+                profile: PlayerProfile::default()
+            })
+            .collect();
+        EventData {
+            slug: "tournament/w/event/singles".to_string(),
+            name: "W".to_string(),
+            num_entrants: 6,
+            start_date: None,
+            videogame: None,
+            standings,
+            sets: vec![
+                set("A", "B", Some(2), Some(1)),
+                set("A", "C", Some(0), Some(-1)),
+                set("C", "D", Some(0), Some(-1)),
+                set("B", "D", Some(0), Some(-1)),
+                set("A", "E", None, None)
+            ]
+        }
+    }
+
+    #[test]
+    fn only_entrants_dqd_out_of_every_set_are_left_out() {
+        let event = event_with_dqs();
+        assert_eq!(event.dq_only_entrants(), vec!["D".to_string()]);
+
+        let mut pr = PowerRankings::with_config(ScoringConfig::default(), QualificationConfig::default());
+        let id = pr.add_event(&event, None, 1.0);
+        assert_eq!(pr.tournaments()[id as usize].excluded, vec!["D".to_string()]);
+        assert_eq!(pr.tournaments()[id as usize].tournament_entrants, 6);
+        assert!(!pr.players.contains(&Player::new("D")));
+        assert_eq!(pr.players.len(), 5);
+
+        let scoring = ScoringConfig { exclude_dqs: false, ..ScoringConfig::default() };
+        let mut pr = PowerRankings::with_config(scoring, QualificationConfig::default());
+        pr.add_event(&event, None, 1.0);
+        assert_eq!(pr.players.len(), 6);
+    }
+
+    /// Two doubles teams: "zed" and "Amy" placed 1st, "Bo" and "Cy" 2nd.
+    fn doubles_event() -> EventData {
+        let standings = [("zed", 1, "t1"), ("Amy", 1, "t1"), ("Bo", 2, "t2"), ("Cy", 2, "t2")].iter()
+            .map(|(name, placement, team)| EventStanding {
+                name: name.to_string(),
+                player_id: None,
+                placement: *placement,
+                seed: None,
+                team: Some(team.to_string()),
+                profile: PlayerProfile::default()
+            })
+            .collect();
+        EventData {
+            slug: "tournament/w/event/doubles".to_string(),
+            name: "W Doubles".to_string(),
+            num_entrants: 2,
+            start_date: None,
+            videogame: None,
+            standings,
+            sets: vec![set("zed", "Bo", Some(3), Some(1))]
+        }
+    }
+
+    fn placements_by_name(pr: &PowerRankings) -> Vec<(String, Vec<(u32, u32)>)> {
+        let mut players: Vec<(String, Vec<(u32, u32)>)> = pr.players.iter()
+            .map(|p| (p.get_name().to_string(), p.get_placements()))
+            .collect();
+        players.sort();
+        players
+    }
+
+    #[test]
+    fn teams_are_ranked_as_individuals_or_units() {
+        let event = doubles_event();
+        assert_eq!(event.entrants().len(), 2);
+
+        let mut pr = PowerRankings::with_config(ScoringConfig::default(), QualificationConfig::default());
+        pr.add_event(&event, None, 1.0);
+        assert_eq!(placements_by_name(&pr), vec![
+            ("Amy".to_string(), vec![(0, 1)]),
+            ("Bo".to_string(), vec![(0, 2)]),
+            ("Cy".to_string(), vec![(0, 2)]),
+            ("zed".to_string(), vec![(0, 1)])
+        ]);
+
+        let scoring = ScoringConfig { teams: TeamMode::Units, ..ScoringConfig::default() };
+        let mut pr = PowerRankings::with_config(scoring, QualificationConfig::default());
+        pr.add_event(&event, None, 1.0);
+        assert_eq!(placements_by_name(&pr), vec![
+            ("Amy / zed".to_string(), vec![(0, 1)]),
+            ("Bo / Cy".to_string(), vec![(0, 2)])
+        ]);
+    }
+
+    #[test]
+    fn a_team_dqd_out_of_every_set_is_left_out_together() {
+        let mut event = doubles_event();
+        event.sets[0].loser_score = Some(-1);
+        assert_eq!(event.dq_only_entrants(), vec!["Bo".to_string(), "Cy".to_string()]);
+    }
+
+    #[test]
+    fn ineligible_players_keep_their_placements() {
+        let config = crate::config::EligibilityConfig { affiliations: vec!["RPI".to_string()], ..Default::default() };
+        let eligibility = Eligibility::load(&config, std::path::Path::new(".")).unwrap();
+        let mut event = doubles_event();
+        for member in event.standings.iter_mut().filter(|s| s.name != "zed") {
+            member.profile.prefix = Some("RPI".to_string());
+        }
+
+        let mut pr = PowerRankings::with_config(ScoringConfig::default(), QualificationConfig::default());
+        pr.set_eligibility(eligibility.clone());
+        pr.add_event(&event, None, 1.0);
+        assert_eq!(pr.ineligible_players(), vec!["zed".to_string()]);
+        assert!(pr.players.iter().any(|p| p.get_name() == "zed" && p.get_placements() == vec![(0, 1)]));
+
+        // a unit is ineligible if any member is
+        let scoring = ScoringConfig { teams: TeamMode::Units, ..ScoringConfig::default() };
+        let mut pr = PowerRankings::with_config(scoring, QualificationConfig::default());
+        pr.set_eligibility(eligibility);
+        pr.add_event(&event, None, 1.0);
+        assert_eq!(pr.ineligible_players(), vec!["Amy / zed".to_string()]);
+    }
+}
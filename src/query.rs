@@ -1,8 +1,13 @@
-use crate::prcalc::*;
+use crate::input::{EventEntry, EventTarget};
+use crate::prcalc::{EventData, EventSet, EventStanding, PlayerProfile};
+use crate::source::TournamentSource;
+use chrono::DateTime;
+use futures::executor::block_on;
 use std::error::Error;
+use std::fmt;
 use graphql_client::{GraphQLQuery, Response};
 use lazy_static::lazy_static;
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 
 const STARTGG_ENDPOINT: &str = "https://api.start.gg/gql/alpha";
 
@@ -16,70 +21,472 @@ pub struct TournamentQuery;
 
 pub type TournamentQueryVariables = tournament_query::Variables;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "api/schema.json",
+    query_path = "api/tournamentevents.graphql",
+    response_derives = "Debug"
+)]
+pub struct TournamentEventsQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "api/schema.json",
+    query_path = "api/discover.graphql",
+    response_derives = "Debug"
+)]
+pub struct DiscoverTournamentsQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "api/schema.json",
+    query_path = "api/discover.graphql",
+    response_derives = "Debug"
+)]
+pub struct DiscoverLeagueEventsQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "api/schema.json",
+    query_path = "api/eventsets.graphql",
+    response_derives = "Debug"
+)]
+pub struct EventSetsQuery;
+
+// start.gg timestamps are unix seconds
+pub type Timestamp = i64;
+
+const DISCOVER_PAGE_SIZE: i64 = 20;
+// sets are nested deeply enough that bigger pages hit start.gg's complexity limit
+const SETS_PAGE_SIZE: i64 = 40;
+
 lazy_static!{
     pub static ref CLIENT_THREADPOOL: Client = Client::new();
 }
 
-fn add_standings_to_pr(data: &Response<tournament_query::ResponseData>, pr: &mut PowerRankings) {
-    let inner_data = match &data.data {
-        Some(d) => d,
-        None => {
-            println!("There is no data in this response!");
-            return;
+/// Everything that can go wrong while loading a single event from a bracket site.
+#[derive(Debug)]
+pub enum QueryError {
+    /// The site rejected our token. Nothing else from it will load either.
+    Auth { slug: String, message: String },
+    /// The response carried a GraphQL `errors` array (bad slug, complexity limit, ...).
+    GraphQL { slug: String, messages: Vec<String> },
+    /// The response parsed, but the part we needed wasn't in it.
+    MissingData { slug: String, what: &'static str },
+    /// A tournament has no event for the game it was looked up for.
+    NoEventForGame { slug: String },
+    /// The request itself failed or the body wasn't the JSON we expected.
+    Http { slug: String, source: reqwest::Error },
+    /// A canned response was missing or unreadable (see source.rs).
+    Fixture { slug: String, message: String }
+}
+
+impl QueryError {
+    pub fn is_auth(&self) -> bool {
+        matches!(self, QueryError::Auth { .. })
+    }
+
+    pub fn slug(&self) -> &str {
+        match self {
+            QueryError::Auth { slug, .. } => slug,
+            QueryError::GraphQL { slug, .. } => slug,
+            QueryError::MissingData { slug, .. } => slug,
+            QueryError::NoEventForGame { slug } => slug,
+            QueryError::Http { slug, .. } => slug,
+            QueryError::Fixture { slug, .. } => slug
         }
-    };
+    }
+}
 
-    let event = match &inner_data.event {
-        Some(e) => e,
-        None => {
-            println!("There is no event in this response!");
-            return;
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryError::Auth { slug, message } =>
+                write!(f, "{}: the API token was rejected ({})", slug, message),
+            QueryError::GraphQL { slug, messages } =>
+                write!(f, "{}: start.gg returned errors: {}", slug, messages.join("; ")),
+            QueryError::MissingData { slug, what } =>
+                write!(f, "{}: there is no {} in this response", slug, what),
+            QueryError::NoEventForGame { slug } =>
+                write!(f, "{}: the tournament has no event for the requested game", slug),
+            QueryError::Http { slug, source } =>
+                write!(f, "{}: request failed: {}", slug, source),
+            QueryError::Fixture { slug, message } =>
+                write!(f, "{}: {}", slug, message)
         }
+    }
+}
+
+impl Error for QueryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            QueryError::Http { source, .. } => Some(source),
+            _ => None
+        }
+    }
+}
+
+fn looks_like_auth_failure(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("authentication") || message.contains("unauthorized") || message.contains("auth token")
+}
+
+fn check_graphql_errors<T>(slug: &str, data: &Response<T>) -> Result<(), QueryError> {
+    let errors = match &data.errors {
+        Some(e) if !e.is_empty() => e,
+        _ => return Ok(())
     };
 
-    // Add tournament
+    let messages: Vec<String> = errors.iter().map(|e| e.message.clone()).collect();
+    if let Some(auth) = messages.iter().find(|m| looks_like_auth_failure(m)) {
+        return Err(QueryError::Auth { slug: slug.to_string(), message: auth.clone() });
+    }
+    Err(QueryError::GraphQL { slug: slug.to_string(), messages })
+}
 
-    let tournament_name = event.name.as_ref().unwrap().clone();
-    let tournament_entrants = event.num_entrants.unwrap() as u32;
-    let tournament_id = pr.add_tournament(tournament_name, tournament_entrants);
+pub fn event_from_response(slug: &str, data: &Response<tournament_query::ResponseData>) -> Result<EventData, QueryError> {
+    check_graphql_errors(slug, data)?;
 
-    // End Add Tournament
+    let missing = |what| QueryError::MissingData { slug: slug.to_string(), what };
 
-    let standings = match &event.standings {
-        Some(s) => s,
-        None => {
-            println!("There are no standings in this response!");
-            return;
-        }
+    let inner_data = data.data.as_ref().ok_or_else(|| missing("data"))?;
+    let event = inner_data.event.as_ref().ok_or_else(|| missing("event"))?;
+    let standings = event.standings.as_ref().ok_or_else(|| missing("standings"))?;
+
+    let mut event_data = EventData {
+        slug: slug.to_string(),
+        name: event.name.clone().ok_or_else(|| missing("event name"))?,
+        num_entrants: event.num_entrants.ok_or_else(|| missing("entrant count"))? as u32,
+        start_date: event.start_at
+            .and_then(|t| DateTime::from_timestamp(t, 0))
+            .map(|d| d.date_naive()),
+        videogame: event.videogame.as_ref()
+            .and_then(|v| v.id.as_ref())
+            .and_then(|id| id.parse().ok()),
+        standings: vec![],
+        sets: vec![]
     };
 
-    for opt_player in standings.nodes.as_ref().unwrap() {
-        if let Some(player) = opt_player {
-            let player_name = player.player.as_ref().unwrap().gamer_tag.as_ref().unwrap().clone();
-            let placement = player.placement.unwrap() as u32;
-            // println!("Player: {:20}   Placement: {:4}",
-                // player_name,
-                // placement);
+    for standing in standings.nodes.as_ref().ok_or_else(|| missing("standings list"))?.iter().flatten() {
+        let placement = standing.placement.ok_or_else(|| missing("placement"))? as u32;
+        let entrant = standing.entrant.as_ref();
+        let seed = entrant.and_then(|e| e.initial_seed_num).map(|s| s as u32);
+
+        // (tag, player ID, profile) for everyone on the entrant, more than one on a team
+        let mut members: Vec<(String, Option<String>, PlayerProfile)> = entrant
+            .and_then(|e| e.participants.as_ref())
+            .into_iter().flatten().flatten()
+            .filter_map(|p| p.player.as_ref())
+            .filter_map(|p| {
+                let location = p.user.as_ref().and_then(|u| u.location.as_ref());
+                let profile = PlayerProfile {
+                    prefix: p.prefix.clone().filter(|prefix| !prefix.is_empty()),
+                    city: location.and_then(|l| l.city.clone()),
+                    state: location.and_then(|l| l.state.clone()),
+                    country: location.and_then(|l| l.country.clone())
+                };
+                Some((p.gamer_tag.clone()?, p.id.clone(), profile))
+            })
+            .collect();
+        if members.is_empty() {
+            let player = standing.player.as_ref().ok_or_else(|| missing("player"))?;
+            let tag = player.gamer_tag.clone().ok_or_else(|| missing("player tag"))?;
+            members.push((tag, player.id.clone(), PlayerProfile::default()));
+        }
+        let team = if members.len() > 1 { entrant.and_then(|e| e.id.clone()) } else { None };
+
+        for (name, player_id, profile) in members {
+            event_data.standings.push(EventStanding { name, player_id, placement, seed, team: team.clone(), profile });
+        }
+    }
+
+    Ok(event_data)
+}
+
+/// An event found by `discover`, before a TO has looked at it.
+#[derive(Debug, Clone)]
+pub struct DiscoveredEvent {
+    pub slug: String,
+    pub tournament_name: String,
+    pub event_name: String,
+    pub start_at: Option<Timestamp>,
+    pub num_entrants: Option<i64>
+}
+
+pub struct TournamentSearch {
+    pub owner_id: Option<String>,
+    /// `("lat,lng", "25mi")`
+    pub location: Option<(String, String)>,
+    pub after_date: Option<Timestamp>,
+    pub before_date: Option<Timestamp>,
+    pub videogame_id: u32
+}
+
+/// start.gg's GraphQL API, or a stand-in for it at another URL.
+pub struct StartGg {
+    endpoint: String
+}
+
+impl StartGg {
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        StartGg { endpoint: endpoint.into() }
+    }
+
+    /// The real API, or STARTGG_API_URL's stand-in.
+    pub fn from_env() -> Self {
+        StartGg::new(std::env::var("STARTGG_API_URL").unwrap_or_else(|_| STARTGG_ENDPOINT.to_string()))
+    }
+
+    async fn post_query<Q: GraphQLQuery>(&self, slug: &str, variables: Q::Variables) -> Result<Response<Q::ResponseData>, QueryError> {
+        let http_err = |source| QueryError::Http { slug: slug.to_string(), source };
+
+        // this is the important line
+        let request_body = Q::build_query(variables);
+
+        let res = CLIENT_THREADPOOL.post(&self.endpoint)
+            .bearer_auth("c3b341cc8234d141f75fee5e48ccb953")
+            .json(&request_body)
+            .send();
+
+        let res = res.await.map_err(http_err)?;
+        if res.status() == StatusCode::UNAUTHORIZED || res.status() == StatusCode::FORBIDDEN {
+            let status = res.status().to_string();
+            let message = res.text().await.unwrap_or(status);
+            return Err(QueryError::Auth { slug: slug.to_string(), message });
+        }
+        res.json().await.map_err(http_err)
+    }
+
+    /// Fetches one event's standings.
+    pub async fn do_query(&self, variables: tournament_query::Variables) -> Result<EventData, QueryError> {
+        let slug = variables.event_slug.clone().unwrap_or_default();
+        let response_body = self.post_query::<TournamentQuery>(&slug, variables).await?;
+
+        event_from_response(&slug, &response_body)
+    }
+
+    /// Finds the event slug for `videogame_id` at a tournament. If the tournament
+    /// runs more than one event for that game, the biggest one is taken.
+    pub async fn resolve_tournament_event(&self, tournament_slug: &str, videogame_id: u32) -> Result<String, QueryError> {
+        let variables = tournament_events_query::Variables {
+            tournament_slug: Some(tournament_slug.to_string()),
+            videogame_id: Some(vec![Some(videogame_id.to_string())])
+        };
+        let response_body = self.post_query::<TournamentEventsQuery>(tournament_slug, variables).await?;
+        event_slug_from_response(tournament_slug, &response_body)
+    }
+
+    /// Resolves an input file entry to an event and fetches it.
+    pub async fn fetch_event(&self, entry: &EventEntry) -> Result<EventData, QueryError> {
+        let event_slug = match &entry.target {
+            EventTarget::Event(slug) => slug.clone(),
+            EventTarget::Tournament { slug, videogame_id } => self.resolve_tournament_event(slug, *videogame_id).await?,
+            EventTarget::File { slug, .. } | EventTarget::Challonge(slug) =>
+                return Err(QueryError::MissingData { slug: slug.clone(), what: "start.gg event" })
+        };
+
+        let mut event = self.do_query(TournamentQueryVariables { event_slug: Some(event_slug.clone()) }).await?;
+        event.sets = self.fetch_event_sets(&event_slug).await?;
+        Ok(event)
+    }
 
-            pr.add_player(player_name.clone());
-            pr.add_placement_to_player(player_name.clone(), tournament_id, placement).unwrap();
+    /// Pages through every completed set of an event. Sets missing an entrant
+    /// (byes, unplayed sets) are left out.
+    pub async fn fetch_event_sets(&self, slug: &str) -> Result<Vec<EventSet>, QueryError> {
+        let mut sets = vec![];
+        let mut page: i64 = 1;
+        loop {
+            let variables = event_sets_query::Variables {
+                event_slug: Some(slug.to_string()),
+                page: Some(page),
+                per_page: Some(SETS_PAGE_SIZE)
+            };
+            let response_body = self.post_query::<EventSetsQuery>(slug, variables).await?;
+            let (page_sets, total_pages) = sets_from_response(slug, response_body)?;
+            sets.extend(page_sets);
+            if page >= total_pages { break; }
+            page += 1;
         }
+        Ok(sets)
+    }
+
+    /// Pages through `tournaments(query: {filter: ...})`, returning every event
+    /// for the requested game.
+    pub async fn discover_tournament_events(&self, search: &TournamentSearch, description: &str)
+            -> Result<Vec<DiscoveredEvent>, QueryError> {
+        let mut found = vec![];
+        let mut page: i64 = 1;
+        loop {
+            let variables = discover_tournaments_query::Variables {
+                page: Some(page),
+                per_page: Some(DISCOVER_PAGE_SIZE),
+                owner_id: search.owner_id.clone(),
+                location: search.location.as_ref().map(|(from, distance)| {
+                    discover_tournaments_query::TournamentLocationFilter {
+                        distance_from: Some(from.clone()),
+                        distance: Some(distance.clone())
+                    }
+                }),
+                after_date: search.after_date,
+                before_date: search.before_date,
+                videogame_ids: Some(vec![Some(search.videogame_id.to_string())])
+            };
+            let response_body = self.post_query::<DiscoverTournamentsQuery>(description, variables).await?;
+            check_graphql_errors(description, &response_body)?;
+
+            let connection = response_body.data
+                .and_then(|d| d.tournaments)
+                .ok_or(QueryError::MissingData { slug: description.to_string(), what: "tournaments" })?;
+
+            for tournament in connection.nodes.into_iter().flatten().flatten() {
+                let tournament_name = tournament.name.unwrap_or_default();
+                for event in tournament.events.into_iter().flatten().flatten() {
+                    if let Some(slug) = event.slug {
+                        found.push(DiscoveredEvent {
+                            slug,
+                            tournament_name: tournament_name.clone(),
+                            event_name: event.name.unwrap_or_default(),
+                            start_at: event.start_at,
+                            num_entrants: event.num_entrants
+                        });
+                    }
+                }
+            }
+
+            let total_pages = connection.page_info.and_then(|p| p.total_pages).unwrap_or(0);
+            if page >= total_pages { break; }
+            page += 1;
+        }
+        Ok(found)
+    }
+
+    /// Pages through every event of a league, keeping the ones for the requested
+    /// game that start inside the search's date range.
+    pub async fn discover_league_events(&self, league: &str, search: &TournamentSearch)
+            -> Result<Vec<DiscoveredEvent>, QueryError> {
+        let (league_id, league_slug) = match league.parse::<u64>() {
+            Ok(_) => (Some(league.to_string()), None),
+            Err(_) => (None, Some(league.to_string()))
+        };
+        let description = format!("league {}", league);
+
+        let mut found = vec![];
+        let mut page: i64 = 1;
+        loop {
+            let variables = discover_league_events_query::Variables {
+                league_id: league_id.clone(),
+                league_slug: league_slug.clone(),
+                page: Some(page),
+                per_page: Some(DISCOVER_PAGE_SIZE)
+            };
+            let response_body = self.post_query::<DiscoverLeagueEventsQuery>(&description, variables).await?;
+            check_graphql_errors(&description, &response_body)?;
+
+            let connection = response_body.data
+                .and_then(|d| d.league)
+                .and_then(|l| l.events)
+                .ok_or(QueryError::MissingData { slug: description.clone(), what: "league" })?;
+
+            for event in connection.nodes.into_iter().flatten().flatten() {
+                let game = event.videogame.as_ref().and_then(|v| v.id.clone());
+                if game != Some(search.videogame_id.to_string()) { continue; }
+                if let (Some(after), Some(start)) = (search.after_date, event.start_at) {
+                    if start < after { continue; }
+                }
+                if let (Some(before), Some(start)) = (search.before_date, event.start_at) {
+                    if start > before { continue; }
+                }
+
+                if let Some(slug) = event.slug {
+                    found.push(DiscoveredEvent {
+                        slug,
+                        tournament_name: event.tournament.and_then(|t| t.name).unwrap_or_default(),
+                        event_name: event.name.unwrap_or_default(),
+                        start_at: event.start_at,
+                        num_entrants: event.num_entrants
+                    });
+                }
+            }
+
+            let total_pages = connection.page_info.and_then(|p| p.total_pages).unwrap_or(0);
+            if page >= total_pages { break; }
+            page += 1;
+        }
+        Ok(found)
+    }
+}
+
+impl TournamentSource for StartGg {
+    fn handles(&self, target: &EventTarget) -> bool {
+        matches!(target, EventTarget::Event(_) | EventTarget::Tournament { .. })
+    }
+
+    fn load_event(&self, entry: &EventEntry) -> Result<EventData, QueryError> {
+        block_on(self.fetch_event(entry))
     }
 }
 
+/// The biggest event in a `TournamentEventsQuery` response.
+pub fn event_slug_from_response(tournament_slug: &str, response_body: &Response<tournament_events_query::ResponseData>)
+        -> Result<String, QueryError> {
+    check_graphql_errors(tournament_slug, response_body)?;
 
-pub async fn do_query(variables: tournament_query::Variables, pr: &mut PowerRankings) -> Result<(), Box<dyn Error>> {
-    // this is the important line
-    let request_body = TournamentQuery::build_query(variables);
+    let missing = |what| QueryError::MissingData { slug: tournament_slug.to_string(), what };
+    let tournament = response_body.data.as_ref()
+        .and_then(|d| d.tournament.as_ref())
+        .ok_or_else(|| missing("tournament"))?;
 
-    let res = CLIENT_THREADPOOL.post(STARTGG_ENDPOINT)
-        .bearer_auth("c3b341cc8234d141f75fee5e48ccb953")
-        .json(&request_body)
-        .send();
+    tournament.events.iter().flatten().flatten()
+        .filter(|e| e.slug.is_some())
+        .max_by_key(|e| e.num_entrants.unwrap_or(0))
+        .and_then(|e| e.slug.clone())
+        .ok_or_else(|| QueryError::NoEventForGame { slug: tournament_slug.to_string() })
+}
+
+/// One page of an `EventSetsQuery` response, and how many pages there are.
+pub fn sets_from_response(slug: &str, response_body: Response<event_sets_query::ResponseData>)
+        -> Result<(Vec<EventSet>, i64), QueryError> {
+    let mut sets = vec![];
+    check_graphql_errors(slug, &response_body)?;
+
+    let connection = response_body.data
+        .and_then(|d| d.event)
+        .and_then(|e| e.sets)
+        .ok_or(QueryError::MissingData { slug: slug.to_string(), what: "sets" })?;
 
-    let res = res.await?;
-    let response_body: Response<tournament_query::ResponseData> = res.json().await?;
+    for set in connection.nodes.into_iter().flatten().flatten() {
+        let winner_entrant = match set.winner_id {
+            Some(id) => id.to_string(),
+            None => continue
+        };
+        // (entrant ID, tag, player ID, score) for each side
+        let sides: Vec<(String, String, Option<String>, Option<i32>)> = set.slots.into_iter().flatten().flatten()
+            .filter_map(|slot| {
+                let entrant = slot.entrant?;
+                let player = entrant.participants.into_iter().flatten().flatten().next()?.player?;
+                let score = slot.standing
+                    .and_then(|s| s.stats)
+                    .and_then(|s| s.score)
+                    .and_then(|s| s.value)
+                    .map(|v| v as i32);
+                Some((entrant.id?, player.gamer_tag?, player.id, score))
+            })
+            .collect();
+        let winner = sides.iter().find(|s| s.0 == winner_entrant);
+        let loser = sides.iter().find(|s| s.0 != winner_entrant);
+        if let (Some(w), Some(l)) = (winner, loser) {
+            sets.push(EventSet {
+                round: set.full_round_text.unwrap_or_default(),
+                winner: w.1.clone(),
+                winner_id: w.2.clone(),
+                loser: l.1.clone(),
+                loser_id: l.2.clone(),
+                winner_score: w.3,
+                loser_score: l.3
+            });
+        }
+    }
 
-    add_standings_to_pr(&response_body, pr);
-    Ok(())
-}
\ No newline at end of file
+    let total_pages = connection.page_info.and_then(|p| p.total_pages).unwrap_or(0);
+    Ok((sets, total_pages))
+}
@@ -0,0 +1,301 @@
+// Markdown and HTML reports, for `--format markdown|html`.
+//
+// Both are rendered into a template (templates/report.md and
+// templates/report.html by default, `--template` to use another) by replacing
+// these placeholders:
+//
+//     {{title}}         "Power Rankings", plus the season name if there is one
+//     {{generated}}     the date the report was made
+//     {{methodology}}   point table, qualification rules and event weighting
+//     {{events}}        every event and the multiplier it ended up with
+//     {{rankings}}      one table per ranking method
+//
+// When a season ranks several games, each section holds every game in turn
+// under a heading of its own.
+//
+// The HTML page is self-contained: the default template carries the styling
+// and the script that sorts tables when a column header is clicked.
+
+use crate::export::{METHODS, Methodology, MethodResults, RankEntry, RankingResults};
+use chrono::Utc;
+use std::io::{self, Write};
+
+pub const DEFAULT_MARKDOWN_TEMPLATE: &str = include_str!("../templates/report.md");
+pub const DEFAULT_HTML_TEMPLATE: &str = include_str!("../templates/report.html");
+
+pub fn write_markdown(f: &mut dyn Write, all: &[RankingResults], template: &str) -> io::Result<()> {
+    let (mut methodology, mut events, mut rankings) = (String::new(), String::new(), String::new());
+    for results in all {
+        let heading = results.game.as_ref()
+            .map(|g| format!("### {}\n\n", markdown_escape(g)))
+            .unwrap_or_default();
+        let level = if results.game.is_some() { "####" } else { "###" };
+        let methods: Vec<String> = results.methods.iter().map(|m| markdown_method(m, level)).collect();
+        methodology += &(heading.clone() + &markdown_methodology(&results.methodology) + "\n");
+        events += &(heading.clone() + &markdown_events(results) + "\n");
+        rankings += &(heading + &methods.join("\n") + "\n");
+    }
+    let page = fill_template(template, season(all), markdown_escape, &[
+        ("methodology", methodology.trim_end().to_string() + "\n"),
+        ("events", events.trim_end().to_string() + "\n"),
+        ("rankings", rankings.trim_end().to_string() + "\n")
+    ]);
+    f.write_all(page.as_bytes())
+}
+
+pub fn write_html(f: &mut dyn Write, all: &[RankingResults], template: &str) -> io::Result<()> {
+    let (mut methodology, mut events, mut rankings) = (String::new(), String::new(), String::new());
+    for results in all {
+        let heading = results.game.as_ref()
+            .map(|g| format!("<h3>{}</h3>\n", html_escape(g)))
+            .unwrap_or_default();
+        methodology += &(heading.clone() + &html_methodology(&results.methodology));
+        events += &(heading.clone() + &html_events(results));
+        rankings += &heading;
+        for method in &results.methods {
+            rankings += &html_method(results, method);
+        }
+    }
+    let page = fill_template(template, season(all), html_escape, &[
+        ("methodology", methodology),
+        ("events", events),
+        ("rankings", rankings)
+    ]);
+    f.write_all(page.as_bytes())
+}
+
+fn season(all: &[RankingResults]) -> Option<&str> {
+    all.first().and_then(|r| r.season.as_deref())
+}
+
+/// Replaces each `{{name}}` in the template in a single pass, so placeholders
+/// in the inserted text are left as they are. `escape` makes the season name
+/// safe to put in the page; the sections are escaped already.
+fn fill_template(template: &str, season: Option<&str>, escape: fn(&str) -> String,
+        sections: &[(&str, String)]) -> String {
+    let title = match season {
+        Some(season) => format!("Power Rankings: {}", escape(season)),
+        None => "Power Rankings".to_string()
+    };
+    let generated = Utc::now().date_naive().to_string();
+
+    let mut page = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        page += &rest[..start];
+        let after = &rest[start + 2..];
+        let filled = after.find("}}").and_then(|end| {
+            let text = match &after[..end] {
+                "title" => Some(title.as_str()),
+                "generated" => Some(generated.as_str()),
+                name => sections.iter().find(|(n, _)| *n == name).map(|(_, text)| text.as_str())
+            };
+            text.map(|text| (text, &after[end + 2..]))
+        });
+        match filled {
+            Some((text, remaining)) => {
+                page += text;
+                rest = remaining;
+            },
+            None => {
+                page += "{{";
+                rest = after;
+            }
+        }
+    }
+    page + rest
+}
+
+/// The sentences the methodology section is made of, shared by both formats.
+fn methodology_lines(m: &Methodology) -> Vec<String> {
+    let mut lines = vec![
+        format!("Players need at least {} events to be ranked.", m.minimum_events),
+        format!("Weighted points scale each result by the event's entrants divided by {}; \
+            median and mean points divide by the median and mean event size instead.", m.minimum_entrants),
+        "The overall PR orders players by their average rank across the average placement \
+            and the four points methods.".to_string()
+    ];
+    if m.drop_worst {
+        lines.push(format!("Each player's worst result is dropped once they've entered more than {} events.",
+            m.minimum_events));
+    }
+    let tiers: Vec<String> = m.tiers.iter().map(|(t, x)| format!("{} x{:.2}", t, x)).collect();
+    lines.push(format!("Event tiers multiply points: {}. Events without a tier count x1.00.", tiers.join(", ")));
+    lines.extend(m.decay.clone());
+    lines.extend(m.field_strength.clone());
+    lines.extend(m.dqs.clone());
+    lines.extend(m.teams.clone());
+    lines.extend(m.eligibility.clone());
+    lines.push(m.percentile.clone());
+    lines.push("Seed performance is how many placement buckets above or below their seed a player finished.".to_string());
+    lines
+}
+
+fn score_text(method: &MethodResults, entry: &RankEntry) -> String {
+    match method.key.as_str() {
+        "seed_performance" => format!("{:+.2}", entry.score),
+        _ => format!("{:.4}", entry.score)
+    }
+}
+
+fn spr_text(entry: &RankEntry) -> String {
+    entry.seed_performance.map(|s| format!("{:+.2}", s)).unwrap_or_else(|| "n/a".to_string())
+}
+
+fn optional_text<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())
+}
+
+/* ---- MARKDOWN ---- */
+
+fn markdown_escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if matches!(c, '\\' | '|' | '*' | '_' | '`' | '[' | ']' | '<' | '>') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn markdown_methodology(m: &Methodology) -> String {
+    let mut out = String::new();
+    for line in methodology_lines(m) {
+        out += &format!("- {}\n", markdown_escape(&line));
+    }
+    out += "\n| Placement | Points |\n|---:|---:|\n";
+    for (placement, points) in &m.points {
+        out += &format!("| {} | {:.2} |\n", placement, points);
+    }
+    out += &format!("| other | {:.2} |\n", m.other_points);
+    out
+}
+
+fn markdown_events(results: &RankingResults) -> String {
+    let mut out = "| Event | Date | Entrants | Strength | Tier | Weight | Decay | Final |\n\
+        |---|---|---:|---:|---:|---:|---:|---:|\n".to_string();
+    for e in &results.events {
+        out += &format!("| {} | {} | {} | {:.2} | {} | {:.2} | {:.2} | {:.2} |\n",
+            markdown_escape(&e.name), optional_text(e.date), e.entrants, e.strength,
+            optional_text(e.tier), e.weight, e.decay, e.multiplier);
+    }
+    out
+}
+
+/// `level` is the heading the method's title goes under, deeper when it sits
+/// under a game's.
+fn markdown_method(method: &MethodResults, level: &str) -> String {
+    let shown = METHODS.iter().find(|m| m.key == method.key).map(|m| m.shown).unwrap_or(usize::MAX);
+    let overall = method.key == "overall";
+    let mut out = format!("{} {}\n\n", level, markdown_escape(&method.title));
+    out += if overall {
+        "| Rank | Player | Score | SPR | Events |\n|---:|---|---:|---:|---:|\n"
+    } else {
+        "| Rank | Player | Score | Events |\n|---:|---|---:|---:|\n"
+    };
+    for entry in method.entries.iter().take(shown) {
+        let spr = if overall { format!(" {} |", spr_text(entry)) } else { String::new() };
+        out += &format!("| {} | {} | {} |{} {} |\n",
+            entry.rank, markdown_escape(&entry.player), score_text(method, entry), spr, entry.events_entered);
+    }
+    if method.entries.len() > shown {
+        out += &format!("\n...and {} more.\n", method.entries.len() - shown);
+    }
+    out
+}
+
+/* ---- HTML ---- */
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn html_methodology(m: &Methodology) -> String {
+    let mut out = "<ul>\n".to_string();
+    for line in methodology_lines(m) {
+        out += &format!("  <li>{}</li>\n", html_escape(&line));
+    }
+    out += "</ul>\n<table class=\"points\">\n  <thead><tr><th>Placement</th><th>Points</th></tr></thead>\n  <tbody>\n";
+    for (placement, points) in &m.points {
+        out += &format!("    <tr><td>{}</td><td>{:.2}</td></tr>\n", placement, points);
+    }
+    out += &format!("    <tr><td>other</td><td>{:.2}</td></tr>\n  </tbody>\n</table>\n", m.other_points);
+    out
+}
+
+fn html_events(results: &RankingResults) -> String {
+    let mut out = "<table class=\"sortable\">\n  <thead><tr><th>Event</th><th>Date</th>\
+        <th class=\"num\">Entrants</th><th class=\"num\">Strength</th><th>Tier</th>\
+        <th class=\"num\">Weight</th><th class=\"num\">Decay</th><th class=\"num\">Final</th></tr></thead>\n  <tbody>\n".to_string();
+    for e in &results.events {
+        out += &format!("    <tr><td>{}</td><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{:.2}</td>\
+            <td>{}</td><td class=\"num\">{:.2}</td><td class=\"num\">{:.2}</td><td class=\"num\">{:.2}</td></tr>\n",
+            html_escape(&e.name), optional_text(e.date), e.entrants, e.strength,
+            optional_text(e.tier), e.weight, e.decay, e.multiplier);
+    }
+    out += "  </tbody>\n</table>\n";
+    out
+}
+
+fn html_method(results: &RankingResults, method: &MethodResults) -> String {
+    let overall = method.key == "overall";
+    // games' sections need their own ids, and their titles sit under the game's
+    let (id, level) = match &results.game {
+        Some(game) => (format!("{}-{}", game.to_lowercase().replace(' ', "-"), method.key), "h4"),
+        None => (method.key.clone(), "h3")
+    };
+    let mut out = format!("<section id=\"{}\">\n<{level}>{}</{level}>\n<table class=\"sortable\">\n  <thead><tr>\
+        <th class=\"num\">Rank</th><th>Player</th><th class=\"num\">Score</th>{}<th class=\"num\">Events</th>\
+        </tr></thead>\n  <tbody>\n",
+        html_escape(&id), html_escape(&method.title),
+        if overall { "<th class=\"num\">SPR</th>" } else { "" });
+    for entry in &method.entries {
+        let spr = if overall { format!("<td class=\"num\">{}</td>", spr_text(entry)) } else { String::new() };
+        out += &format!("    <tr><td class=\"num\">{}</td><td>{}</td><td class=\"num\">{}</td>{}<td class=\"num\">{}</td></tr>\n",
+            entry.rank, html_history(results, entry), score_text(method, entry), spr, entry.events_entered);
+    }
+    out += "  </tbody>\n</table>\n</section>\n";
+    out
+}
+
+/// A player's name that expands into the events behind their score.
+fn html_history(results: &RankingResults, entry: &RankEntry) -> String {
+    let mut out = format!("<details><summary>{}</summary>\n      <table class=\"history\">\
+        <tr><th>Event</th><th>Date</th><th>Placement</th><th>Seed</th><th>Points</th></tr>\n",
+        html_escape(&entry.player));
+    for event in &entry.events {
+        let date = results.events.get(event.event_id as usize).and_then(|e| e.date);
+        let points = event.points.map(|p| format!("{:.2}", p)).unwrap_or_else(|| "-".to_string());
+        let placement = match event.raw_placement {
+            Some(raw) => format!("{} (bracket: {})", event.placement, raw),
+            None => event.placement.to_string()
+        };
+        out += &format!("        <tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            html_escape(&event.event), optional_text(date), placement, optional_text(event.seed), points);
+    }
+    out += "      </table></details>";
+    out
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders_are_filled_once() {
+        let sections = [
+            ("events", "an event called {{rankings}}".to_string()),
+            ("rankings", "a player called {{title}}".to_string())
+        ];
+        let page = fill_template("# {{title}}\n{{events}}\n{{rankings}}\n{{unknown}} {{", Some("<b>s</b>"),
+            html_escape, &sections);
+        assert_eq!(page, "# Power Rankings: &lt;b&gt;s&lt;/b&gt;\nan event called {{rankings}}\n\
+            a player called {{title}}\n{{unknown}} {{");
+    }
+}
@@ -0,0 +1,243 @@
+// `serve`: the rankings as JSON over HTTP, for bots and stream overlays.
+//
+//     GET /seasons                                       every season, its dates and games
+//     GET /seasons/<season>/rankings/<method>?game=      one method's ranking, e.g. overall
+//     GET /seasons/<season>/players/<tag>?game=          a player's events and rank in every method
+//     GET /seasons/<season>/head-to-head?a=<tag>&b=<tag> every set between two players
+//
+// Every season in the project file (or tournaments.txt, as season "default",
+// without one) is ranked once at startup from the database, as `rank
+// --offline` would; nothing is fetched. Restart it after fetching new events.
+// `game` is only needed for seasons that rank several games. Errors come back
+// as `{"error": "..."}`.
+
+use crate::export::{self, METHODS, MethodResults, RankingResults};
+use crate::player;
+use crate::store::{StoredSet, Store};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+#[derive(clap::Args, Debug)]
+pub struct ServeArgs {
+    /// Project file holding the season definitions; tournaments.txt is served
+    /// as season "default" if it doesn't exist
+    #[arg(long, default_value = "pr.toml")]
+    pub config: PathBuf,
+
+    /// Database of fetched events to rank from
+    #[arg(long, default_value = "pr.db")]
+    pub db: PathBuf,
+
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    pub addr: SocketAddr
+}
+
+/// A season's rankings, one per game.
+pub struct Season {
+    pub name: String,
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+    pub results: Vec<RankingResults>
+}
+
+struct AppState {
+    seasons: Vec<Season>,
+    store: Mutex<Store>
+}
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+fn not_found(message: String) -> ApiError {
+    ApiError(StatusCode::NOT_FOUND, message)
+}
+
+#[derive(Debug, Deserialize)]
+struct GameQuery {
+    game: Option<String>
+}
+
+#[derive(Debug, Deserialize)]
+struct HeadToHeadQuery {
+    a: Option<String>,
+    b: Option<String>
+}
+
+#[derive(Debug, Serialize)]
+struct SeasonSummary<'a> {
+    name: &'a str,
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+    games: Vec<&'a str>
+}
+
+#[derive(Debug, Serialize)]
+struct PlayerDetail<'a> {
+    player: &'a str,
+    game: Option<&'a str>,
+    events_entered: u32,
+    seeded_events: u32,
+    seed_performance: Option<f64>,
+    events: Vec<EventDetail<'a>>,
+    methods: Vec<MethodRank<'a>>
+}
+
+#[derive(Debug, Serialize)]
+struct EventDetail<'a> {
+    event_id: u32,
+    event: &'a str,
+    slug: Option<&'a str>,
+    date: Option<NaiveDate>,
+    entrants: Option<u32>,
+    placement: u32,
+    /// The bracket's placement, when `placement` was adjusted for ineligible
+    /// players ahead.
+    raw_placement: Option<u32>,
+    seed: Option<u32>
+}
+
+#[derive(Debug, Serialize)]
+struct MethodRank<'a> {
+    key: &'a str,
+    title: &'a str,
+    rank: u32,
+    score: f64,
+    dropped_event: Option<u32>,
+    /// Points per event ID, for methods that score events one at a time.
+    points: BTreeMap<u32, f64>
+}
+
+#[derive(Debug, Serialize)]
+struct HeadToHead {
+    a: String,
+    b: String,
+    a_wins: usize,
+    b_wins: usize,
+    sets: Vec<StoredSet>
+}
+
+pub async fn run(addr: SocketAddr, seasons: Vec<Season>, store: Store) -> Result<(), String> {
+    let state = Arc::new(AppState { seasons, store: Mutex::new(store) });
+    let app = Router::new()
+        .route("/seasons", get(list_seasons))
+        .route("/seasons/{season}/rankings/{method}", get(ranking))
+        .route("/seasons/{season}/players/{tag}", get(player_detail))
+        .route("/seasons/{season}/head-to-head", get(head_to_head))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(addr).await
+        .map_err(|e| format!("Error listening on {}: {}", addr, e))?;
+    println!("Serving rankings on http://{}", addr);
+    axum::serve(listener, app).await.map_err(|e| format!("Error serving rankings: {}", e))
+}
+
+fn find_season<'a>(state: &'a AppState, name: &str) -> Result<&'a Season, ApiError> {
+    state.seasons.iter().find(|s| s.name == name).ok_or_else(|| {
+        let names: Vec<&str> = state.seasons.iter().map(|s| s.name.as_str()).collect();
+        not_found(format!("No season named \"{}\" (have: {})", name, names.join(", ")))
+    })
+}
+
+fn find_results<'a>(season: &'a Season, game: Option<&str>) -> Result<&'a RankingResults, ApiError> {
+    if game.is_none() && season.results.len() > 1 {
+        let games: Vec<&str> = season.results.iter().filter_map(|r| r.game.as_deref()).collect();
+        return Err(ApiError(StatusCode::BAD_REQUEST, format!(
+            "Season \"{}\" ranks several games, pick one with ?game= (have: {})", season.name, games.join(", "))));
+    }
+    export::pick_game(&season.results, game)
+        .map(|i| &season.results[i])
+        .map_err(|e| not_found(format!("Season \"{}\" {}", season.name, e)))
+}
+
+async fn list_seasons(State(state): State<Arc<AppState>>) -> Response {
+    let summaries: Vec<SeasonSummary> = state.seasons.iter().map(|s| SeasonSummary {
+        name: &s.name,
+        start: s.start,
+        end: s.end,
+        games: s.results.iter().filter_map(|r| r.game.as_deref()).collect()
+    }).collect();
+    Json(summaries).into_response()
+}
+
+async fn ranking(State(state): State<Arc<AppState>>, Path((season, method)): Path<(String, String)>,
+        Query(query): Query<GameQuery>) -> Result<Json<MethodResults>, ApiError> {
+    let results = find_results(find_season(&state, &season)?, query.game.as_deref())?;
+    results.methods.iter().find(|m| m.key == method).cloned().map(Json).ok_or_else(|| {
+        let keys: Vec<&str> = METHODS.iter().map(|m| m.key).collect();
+        not_found(format!("No method \"{}\" (have: {})", method, keys.join(", ")))
+    })
+}
+
+async fn player_detail(State(state): State<Arc<AppState>>, Path((season, tag)): Path<(String, String)>,
+        Query(query): Query<GameQuery>) -> Result<Response, ApiError> {
+    let results = find_results(find_season(&state, &season)?, query.game.as_deref())?;
+    let entries = player::find_entries(results, &tag).map_err(not_found)?;
+    let first = entries[0].1;
+
+    let detail = PlayerDetail {
+        player: &first.player,
+        game: results.game.as_deref(),
+        events_entered: first.events_entered,
+        seeded_events: first.seeded_events,
+        seed_performance: first.seed_performance,
+        events: first.events.iter().map(|event| {
+            let summary = results.events.get(event.event_id as usize);
+            EventDetail {
+                event_id: event.event_id,
+                event: &event.event,
+                slug: summary.map(|s| s.slug.as_str()),
+                date: summary.and_then(|s| s.date),
+                entrants: summary.map(|s| s.entrants),
+                placement: event.placement,
+                raw_placement: event.raw_placement,
+                seed: event.seed
+            }
+        }).collect(),
+        methods: entries.iter().map(|(key, entry)| MethodRank {
+            key,
+            title: player::method_title(results, key),
+            rank: entry.rank,
+            score: entry.score,
+            dropped_event: entry.dropped_event,
+            points: entry.events.iter().filter_map(|e| e.points.map(|p| (e.event_id, p))).collect()
+        }).collect()
+    };
+    Ok(Json(detail).into_response())
+}
+
+async fn head_to_head(State(state): State<Arc<AppState>>, Path(season): Path<String>,
+        Query(query): Query<HeadToHeadQuery>) -> Result<Json<HeadToHead>, ApiError> {
+    let season = find_season(&state, &season)?;
+    let (a, b) = match (query.a, query.b) {
+        (Some(a), Some(b)) => (a, b),
+        _ => return Err(ApiError(StatusCode::BAD_REQUEST, "Give both players, as ?a=<tag>&b=<tag>".to_string()))
+    };
+
+    // SQLite blocks, so it's read off the async workers
+    let (store_state, first, second) = (state.clone(), a.clone(), b.clone());
+    let sets = tokio::task::spawn_blocking(move || store_state.store.lock().unwrap().sets_between(&first, &second))
+        .await
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, format!("Error reading sets: {}", e)))?
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, format!("Error reading sets: {}", e)))?;
+    // only sets at events this season ranks
+    let sets: Vec<StoredSet> = sets.into_iter()
+        .filter(|s| season.results.iter().any(|r| r.events.iter().any(|e| e.slug == s.slug)))
+        .collect();
+    let a_wins = sets.iter().filter(|s| s.first_won).count();
+    Ok(Json(HeadToHead { a, b, a_wins, b_wins: sets.len() - a_wins, sets }))
+}
@@ -0,0 +1,87 @@
+// Where events are fetched from. Each bracket site implements
+// `TournamentSource`, and `rank` hands every listed event to the first source
+// that handles it:
+//
+//     StartGg      tournament/... slugs and start.gg URLs (query.rs)
+//     Challonge    challonge/... slugs and challonge.com URLs (challonge.rs)
+//     Fixtures     both, answered from canned responses (`rank --fixtures`)
+//
+// Sources all produce the same `EventData`, so the store and the rankings
+// never need to know which site an event came from.
+//
+// A fixtures directory holds each site's responses, saved as the site sent
+// them, at paths made from the slugs:
+//
+//     tournament/foo/event/singles.json        TournamentQuery (standings)
+//     tournament/foo/event/singles.sets.json   EventSetsQuery, optional
+//     tournament/bar.json                      TournamentEventsQuery
+//     challonge/club-abc123.json               a Challonge tournament
+
+use crate::challonge;
+use crate::input::{EventEntry, EventTarget};
+use crate::prcalc::EventData;
+use crate::query::{self, QueryError};
+use serde::de::DeserializeOwned;
+use std::path::PathBuf;
+
+pub trait TournamentSource {
+    /// Whether `target` names one of this source's events.
+    fn handles(&self, target: &EventTarget) -> bool;
+
+    /// Fetches the event `entry` points at.
+    fn load_event(&self, entry: &EventEntry) -> Result<EventData, QueryError>;
+}
+
+/// Canned responses, for testing the whole pipeline without a network.
+pub struct Fixtures {
+    dir: PathBuf
+}
+
+impl Fixtures {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Fixtures { dir: dir.into() }
+    }
+
+    fn read<T: DeserializeOwned>(&self, slug: &str, file: &str) -> Result<T, QueryError> {
+        let fixture_err = |message| QueryError::Fixture { slug: slug.to_string(), message };
+        let path = self.dir.join(file);
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| fixture_err(format!("error reading {}: {}", path.display(), e)))?;
+        serde_json::from_str(&text).map_err(|e| fixture_err(format!("error in {}: {}", path.display(), e)))
+    }
+}
+
+impl TournamentSource for Fixtures {
+    fn handles(&self, target: &EventTarget) -> bool {
+        !matches!(target, EventTarget::File { .. })
+    }
+
+    fn load_event(&self, entry: &EventEntry) -> Result<EventData, QueryError> {
+        let event_slug = match &entry.target {
+            EventTarget::Event(slug) => slug.clone(),
+            EventTarget::Tournament { slug, .. } =>
+                query::event_slug_from_response(slug, &self.read(slug, &format!("{}.json", slug))?)?,
+            EventTarget::Challonge(slug) => {
+                let value = self.read(slug, &format!("{}.json", slug))?;
+                return challonge::event_from_export(slug, value)
+                    .map_err(|message| QueryError::Fixture { slug: slug.clone(), message });
+            },
+            EventTarget::File { slug, .. } =>
+                return Err(QueryError::MissingData { slug: slug.clone(), what: "fixture" })
+        };
+
+        let mut event = query::event_from_response(&event_slug,
+            &self.read(&event_slug, &format!("{}.json", event_slug))?)?;
+        let sets_file = format!("{}.sets.json", event_slug);
+        if self.dir.join(&sets_file).is_file() {
+            event.sets = query::sets_from_response(&event_slug, self.read(&event_slug, &sets_file)?)?.0;
+        }
+        // start.gg only answers with the requested game's events
+        if let EventTarget::Tournament { slug, videogame_id } = &entry.target {
+            if event.videogame.is_some_and(|g| g != *videogame_id) {
+                return Err(QueryError::NoEventForGame { slug: slug.clone() });
+            }
+        }
+        Ok(event)
+    }
+}
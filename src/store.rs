@@ -0,0 +1,265 @@
+// Local SQLite database of everything fetched, `pr.db` by default.
+//
+//     events      one row per event, keyed by slug
+//     players     one row per person; `startgg_id` when the site gave one,
+//                 `tag` is the tag from their most recent event, and their
+//                 prefix and location as start.gg last reported them
+//     aliases     every tag a player has been seen under
+//     placements  a player's placement and seed at an event, and the team
+//                 they entered on for doubles
+//     sets        who beat whom at an event, and the score
+//
+// Fetching (or importing, see import.rs) writes events here, and ranking reads them back, so a player who
+// changed tags mid-season is still ranked as one person. `rank --offline`
+// reads only from here.
+
+use crate::input::{EventEntry, EventTarget};
+use crate::prcalc::{EventData, EventSet, EventStanding, PlayerProfile};
+use chrono::{NaiveDate, Utc};
+use rusqlite::{Connection, OptionalExtension, Transaction, params};
+use serde::Serialize;
+use std::path::Path;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS events (
+        id INTEGER PRIMARY KEY,
+        slug TEXT NOT NULL UNIQUE,
+        tournament_slug TEXT,
+        videogame_id INTEGER,
+        name TEXT NOT NULL,
+        num_entrants INTEGER NOT NULL,
+        start_date TEXT,
+        fetched_at TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS players (
+        id INTEGER PRIMARY KEY,
+        startgg_id TEXT UNIQUE,
+        tag TEXT NOT NULL,
+        tag_date TEXT,
+        prefix TEXT,
+        city TEXT,
+        state TEXT,
+        country TEXT
+    );
+    CREATE TABLE IF NOT EXISTS aliases (
+        player_id INTEGER NOT NULL REFERENCES players(id),
+        alias TEXT NOT NULL,
+        PRIMARY KEY (player_id, alias)
+    );
+    CREATE TABLE IF NOT EXISTS placements (
+        event_id INTEGER NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+        player_id INTEGER NOT NULL REFERENCES players(id),
+        tag TEXT NOT NULL,
+        placement INTEGER NOT NULL,
+        seed INTEGER,
+        team TEXT,
+        PRIMARY KEY (event_id, player_id)
+    );
+    CREATE TABLE IF NOT EXISTS sets (
+        id INTEGER PRIMARY KEY,
+        event_id INTEGER NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+        round TEXT NOT NULL,
+        winner_id INTEGER NOT NULL REFERENCES players(id),
+        loser_id INTEGER NOT NULL REFERENCES players(id),
+        winner_score INTEGER,
+        loser_score INTEGER
+    );
+";
+
+pub struct Store {
+    conn: Connection
+}
+
+/// A set between two players, for head-to-head records.
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredSet {
+    pub slug: String,
+    pub event: String,
+    pub date: Option<NaiveDate>,
+    pub round: String,
+    pub winner: String,
+    pub loser: String,
+    pub winner_score: Option<i32>,
+    pub loser_score: Option<i32>,
+    /// Whether the first player asked about won it.
+    pub first_won: bool
+}
+
+impl Store {
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Store { conn })
+    }
+
+    /// Saves a fetched event, replacing whatever was stored for it before.
+    /// `entry` records which tournament slug it was resolved from, so
+    /// `--offline` can find it again, and the game when the site didn't say.
+    pub fn save_event(&mut self, event: &EventData, entry: &EventEntry) -> rusqlite::Result<()> {
+        let tx = self.conn.transaction()?;
+        let (tournament_slug, videogame_id) = match &entry.target {
+            EventTarget::Tournament { slug, videogame_id } => (Some(slug.clone()), Some(*videogame_id)),
+            _ => (None, None)
+        };
+        let videogame_id = event.videogame.or(entry.game).or(videogame_id);
+
+        tx.execute(
+            "INSERT INTO events (slug, tournament_slug, videogame_id, name, num_entrants, start_date, fetched_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(slug) DO UPDATE SET
+                 tournament_slug = COALESCE(excluded.tournament_slug, tournament_slug),
+                 videogame_id = COALESCE(excluded.videogame_id, videogame_id),
+                 name = excluded.name,
+                 num_entrants = excluded.num_entrants,
+                 start_date = excluded.start_date,
+                 fetched_at = excluded.fetched_at",
+            params![event.slug, tournament_slug, videogame_id, event.name, event.num_entrants,
+                event.start_date, Utc::now().to_rfc3339()])?;
+        let event_id: i64 = tx.query_row("SELECT id FROM events WHERE slug = ?1", [&event.slug], |r| r.get(0))?;
+        tx.execute("DELETE FROM placements WHERE event_id = ?1", [event_id])?;
+        tx.execute("DELETE FROM sets WHERE event_id = ?1", [event_id])?;
+
+        for standing in &event.standings {
+            let player_id = player_for(&tx, standing.player_id.as_deref(), &standing.name, event.start_date)?;
+            let p = &standing.profile;
+            if *p != PlayerProfile::default() {
+                tx.execute("UPDATE players SET prefix = ?1, city = ?2, state = ?3, country = ?4 WHERE id = ?5",
+                    params![p.prefix, p.city, p.state, p.country, player_id])?;
+            }
+            tx.execute(
+                "INSERT OR IGNORE INTO placements (event_id, player_id, tag, placement, seed, team) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![event_id, player_id, standing.name, standing.placement, standing.seed, standing.team])?;
+        }
+        for set in &event.sets {
+            let winner = player_for(&tx, set.winner_id.as_deref(), &set.winner, event.start_date)?;
+            let loser = player_for(&tx, set.loser_id.as_deref(), &set.loser, event.start_date)?;
+            tx.execute(
+                "INSERT INTO sets (event_id, round, winner_id, loser_id, winner_score, loser_score)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![event_id, set.round, winner, loser, set.winner_score, set.loser_score])?;
+        }
+        tx.commit()
+    }
+
+    /// The stored event `entry` points at, if it has been fetched before.
+    pub fn cached_event(&self, entry: &EventEntry) -> rusqlite::Result<Option<EventData>> {
+        let slug: Option<String> = match &entry.target {
+            EventTarget::Event(slug) | EventTarget::File { slug, .. } | EventTarget::Challonge(slug) => Some(slug.clone()),
+            EventTarget::Tournament { slug, videogame_id } => self.conn.query_row(
+                "SELECT slug FROM events WHERE tournament_slug = ?1 AND videogame_id = ?2",
+                params![slug, videogame_id], |r| r.get(0)).optional()?
+        };
+        match slug {
+            Some(slug) => self.event(&slug),
+            None => Ok(None)
+        }
+    }
+
+    /// Loads an event with every player under their current tag.
+    pub fn event(&self, slug: &str) -> rusqlite::Result<Option<EventData>> {
+        let row = self.conn.query_row(
+            "SELECT id, name, num_entrants, start_date, videogame_id FROM events WHERE slug = ?1", [slug],
+            |r| Ok((r.get::<_, i64>(0)?, r.get::<_, String>(1)?, r.get::<_, u32>(2)?, r.get::<_, Option<NaiveDate>>(3)?,
+                r.get::<_, Option<u32>>(4)?)))
+            .optional()?;
+        let (event_id, name, num_entrants, start_date, videogame) = match row {
+            Some(r) => r,
+            None => return Ok(None)
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT p.tag, p.startgg_id, pl.placement, pl.seed, pl.team, p.prefix, p.city, p.state, p.country
+             FROM placements pl
+             JOIN players p ON p.id = pl.player_id
+             WHERE pl.event_id = ?1 ORDER BY pl.placement, p.tag")?;
+        let standings = stmt.query_map([event_id], |r| Ok(EventStanding {
+            name: r.get(0)?,
+            player_id: r.get(1)?,
+            placement: r.get(2)?,
+            seed: r.get(3)?,
+            team: r.get(4)?,
+            profile: PlayerProfile {
+                prefix: r.get(5)?,
+                city: r.get(6)?,
+                state: r.get(7)?,
+                country: r.get(8)?
+            }
+        }))?.collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT s.round, w.tag, w.startgg_id, l.tag, l.startgg_id, s.winner_score, s.loser_score FROM sets s
+             JOIN players w ON w.id = s.winner_id
+             JOIN players l ON l.id = s.loser_id
+             WHERE s.event_id = ?1 ORDER BY s.id")?;
+        let sets = stmt.query_map([event_id], |r| Ok(EventSet {
+            round: r.get(0)?,
+            winner: r.get(1)?,
+            winner_id: r.get(2)?,
+            loser: r.get(3)?,
+            loser_id: r.get(4)?,
+            winner_score: r.get(5)?,
+            loser_score: r.get(6)?
+        }))?.collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(Some(EventData { slug: slug.to_string(), name, num_entrants, start_date, videogame, standings, sets }))
+    }
+
+    /// Every set between whoever has played under tags `a` and `b` (case
+    /// doesn't matter), oldest first.
+    pub fn sets_between(&self, a: &str, b: &str) -> rusqlite::Result<Vec<StoredSet>> {
+        let mut stmt = self.conn.prepare(
+            "WITH a AS (SELECT player_id FROM aliases WHERE alias = ?1 COLLATE NOCASE),
+                  b AS (SELECT player_id FROM aliases WHERE alias = ?2 COLLATE NOCASE)
+             SELECT e.slug, e.name, e.start_date, s.round, w.tag, l.tag, s.winner_score, s.loser_score,
+                 s.winner_id IN a
+             FROM sets s
+             JOIN events e ON e.id = s.event_id
+             JOIN players w ON w.id = s.winner_id
+             JOIN players l ON l.id = s.loser_id
+             WHERE (s.winner_id IN a AND s.loser_id IN b) OR (s.winner_id IN b AND s.loser_id IN a)
+             ORDER BY e.start_date, s.id")?;
+        let sets = stmt.query_map(params![a, b], |r| Ok(StoredSet {
+            slug: r.get(0)?,
+            event: r.get(1)?,
+            date: r.get(2)?,
+            round: r.get(3)?,
+            winner: r.get(4)?,
+            loser: r.get(5)?,
+            winner_score: r.get(6)?,
+            loser_score: r.get(7)?,
+            first_won: r.get(8)?
+        }))?.collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(sets)
+    }
+}
+
+/// Finds or creates the player behind a result. Players are matched by
+/// start.gg ID when there is one, and by any tag they've used otherwise.
+fn player_for(tx: &Transaction, startgg_id: Option<&str>, tag: &str, date: Option<NaiveDate>) -> rusqlite::Result<i64> {
+    let existing: Option<(i64, Option<NaiveDate>)> = match startgg_id {
+        Some(id) => tx.query_row("SELECT id, tag_date FROM players WHERE startgg_id = ?1", [id],
+            |r| Ok((r.get(0)?, r.get(1)?))).optional()?,
+        None => tx.query_row(
+            "SELECT p.id, p.tag_date FROM aliases a JOIN players p ON p.id = a.player_id
+             WHERE a.alias = ?1 ORDER BY p.startgg_id IS NOT NULL, p.id LIMIT 1", [tag],
+            |r| Ok((r.get(0)?, r.get(1)?))).optional()?
+    };
+
+    let player_id = match existing {
+        Some((id, tag_date)) => {
+            // the newest event decides the tag a player is ranked under
+            if tag_date.is_none() || date >= tag_date {
+                tx.execute("UPDATE players SET tag = ?1, tag_date = ?2 WHERE id = ?3", params![tag, date, id])?;
+            }
+            id
+        },
+        None => {
+            tx.execute("INSERT INTO players (startgg_id, tag, tag_date) VALUES (?1, ?2, ?3)", params![startgg_id, tag, date])?;
+            tx.last_insert_rowid()
+        }
+    };
+    tx.execute("INSERT OR IGNORE INTO aliases (player_id, alias) VALUES (?1, ?2)", params![player_id, tag])?;
+    Ok(player_id)
+}
+
@@ -0,0 +1,375 @@
+// `tui`: browse a season's rankings in the terminal, and try rule changes on
+// them, for panels deciding a PR.
+//
+//     ←/→ or 1-8     switch between ranking methods
+//     ↑/↓            pick a player; Enter opens their events
+//     s / r          sort by the next column / reverse the sort
+//     Tab            move to the rules, where ↑/↓ picks one and ←/→ changes it
+//     d              toggle drop-worst
+//     u              undo every rule change
+//     q / Esc        quit (or close what's open)
+//
+// Events are read from the database once, as `rank --offline` would, and the
+// season is rescored from them on every rule change. Only drop-worst, the
+// minimum events to qualify and the point table can be changed here; the rest
+// come from the project file.
+
+use crate::config::PointTable;
+use crate::export::{METHODS, RankEntry, RankingResults};
+use crate::prcalc::PowerRankings;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Clear, Paragraph, Row, Table, TableState, Tabs};
+use ratatui::{DefaultTerminal, Frame};
+use std::cmp::Ordering;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(clap::Args, Debug)]
+pub struct TuiArgs {
+    /// Explore a season from the project file instead of tournaments.txt
+    #[arg(long)]
+    pub season: Option<String>,
+
+    /// Project file holding the season definitions
+    #[arg(long, default_value = "pr.toml")]
+    pub config: PathBuf,
+
+    /// Database of fetched events to rank from
+    #[arg(long, default_value = "pr.db")]
+    pub db: PathBuf,
+
+    /// Which game to explore, when the season ranks several
+    #[arg(long)]
+    pub game: Option<String>
+}
+
+/// Points change by this much per keypress.
+const POINTS_STEP: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortColumn {
+    Rank,
+    Player,
+    Score,
+    Events,
+    Seeded,
+    SeedPerformance
+}
+
+impl SortColumn {
+    const ALL: [SortColumn; 6] = [SortColumn::Rank, SortColumn::Player, SortColumn::Score,
+        SortColumn::Events, SortColumn::Seeded, SortColumn::SeedPerformance];
+
+    fn title(&self) -> &'static str {
+        match self {
+            SortColumn::Rank => "Rank",
+            SortColumn::Player => "Player",
+            SortColumn::Score => "Score",
+            SortColumn::Events => "Events",
+            SortColumn::Seeded => "Seeded",
+            SortColumn::SeedPerformance => "SPR"
+        }
+    }
+
+    fn next(&self) -> SortColumn {
+        let i = SortColumn::ALL.iter().position(|c| c == self).unwrap();
+        SortColumn::ALL[(i + 1) % SortColumn::ALL.len()]
+    }
+
+    fn compare(&self, a: &RankEntry, b: &RankEntry) -> Ordering {
+        match self {
+            SortColumn::Rank => a.rank.cmp(&b.rank),
+            SortColumn::Player => a.player.to_lowercase().cmp(&b.player.to_lowercase()),
+            SortColumn::Score => a.score.total_cmp(&b.score),
+            SortColumn::Events => a.events_entered.cmp(&b.events_entered),
+            SortColumn::Seeded => a.seeded_events.cmp(&b.seeded_events),
+            // players without a seed performance go last
+            SortColumn::SeedPerformance => match (a.seed_performance, b.seed_performance) {
+                (Some(x), Some(y)) => x.total_cmp(&y),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Rankings,
+    Player,
+    Rules
+}
+
+/// The rules that can be changed from the TUI.
+#[derive(Debug, Clone, PartialEq)]
+struct Rules {
+    drop_worst: bool,
+    minimum_events: u32,
+    points: PointTable
+}
+
+impl Rules {
+    fn of(pr: &PowerRankings) -> Self {
+        Rules {
+            drop_worst: pr.scoring().drop_worst,
+            minimum_events: pr.qualification().minimum_events,
+            points: pr.scoring().points.clone()
+        }
+    }
+
+    /// One line per rule, in the order they're listed.
+    fn lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("Drop worst      {}", if self.drop_worst { "on" } else { "off" }),
+            format!("Minimum events  {}", self.minimum_events)
+        ];
+        lines.extend(self.points.entries().into_iter()
+            .map(|(placement, points)| format!("Points for {:<4} {:.1}", ordinal(placement), points)));
+        lines
+    }
+
+    /// Changes rule `index` by one step, up or down.
+    fn adjust(&mut self, index: usize, up: bool) {
+        match index {
+            0 => self.drop_worst = !self.drop_worst,
+            1 => self.minimum_events = if up { self.minimum_events + 1 } else { self.minimum_events.saturating_sub(1).max(1) },
+            i => if let Some((placement, points)) = self.points.entries().get(i - 2) {
+                let step = if up { POINTS_STEP } else { -POINTS_STEP };
+                self.points.set(*placement, (points + step).max(0.0));
+            }
+        }
+    }
+}
+
+struct App<F: Fn(PowerRankings) -> RankingResults> {
+    /// Every event added but nothing scored, copied for each rescore.
+    base: PowerRankings,
+    score: F,
+    initial: Rules,
+    rules: Rules,
+    results: RankingResults,
+    method: usize,
+    sort: SortColumn,
+    reverse: bool,
+    table: TableState,
+    focus: Focus,
+    rule: usize
+}
+
+/// Runs the TUI until it's quit. `score` ranks a copy of `base` with the
+/// rules changed, the same way `rank` would.
+pub fn run(base: PowerRankings, score: impl Fn(PowerRankings) -> RankingResults) -> io::Result<()> {
+    let rules = Rules::of(&base);
+    let results = score(base.clone());
+    let mut app = App {
+        base,
+        score,
+        initial: rules.clone(),
+        rules,
+        results,
+        method: METHODS.iter().position(|m| m.key == "overall").unwrap_or(0),
+        sort: SortColumn::Rank,
+        reverse: false,
+        table: TableState::default().with_selected(0),
+        focus: Focus::Rankings,
+        rule: 0
+    };
+
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl<F: Fn(PowerRankings) -> RankingResults> App<F> {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let key = match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                _ => continue
+            };
+
+            match (self.focus, key.code) {
+                (Focus::Rankings, KeyCode::Char('q') | KeyCode::Esc) => return Ok(()),
+                (Focus::Player, KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter) => self.focus = Focus::Rankings,
+                (Focus::Rules, KeyCode::Char('q') | KeyCode::Esc | KeyCode::Tab) => self.focus = Focus::Rankings,
+
+                (Focus::Rankings, KeyCode::Tab) => self.focus = Focus::Rules,
+                (Focus::Rankings, KeyCode::Enter) if self.selected_entry().is_some() => self.focus = Focus::Player,
+                (Focus::Rankings, KeyCode::Up | KeyCode::Char('k')) => self.table.select_previous(),
+                (Focus::Rankings, KeyCode::Down | KeyCode::Char('j')) => self.table.select_next(),
+                (Focus::Rankings, KeyCode::Left | KeyCode::Char('h')) => self.switch_method(self.method + METHODS.len() - 1),
+                (Focus::Rankings, KeyCode::Right | KeyCode::Char('l')) => self.switch_method(self.method + 1),
+                (Focus::Rankings, KeyCode::Char(c @ '1'..='9')) => {
+                    let index = c.to_digit(10).unwrap() as usize - 1;
+                    if index < METHODS.len() {
+                        self.switch_method(index);
+                    }
+                },
+                (Focus::Rankings, KeyCode::Char('s')) => self.resort(|app| app.sort = app.sort.next()),
+                (Focus::Rankings, KeyCode::Char('r')) => self.resort(|app| app.reverse = !app.reverse),
+
+                (Focus::Rules, KeyCode::Up | KeyCode::Char('k')) => self.rule = self.rule.saturating_sub(1),
+                (Focus::Rules, KeyCode::Down | KeyCode::Char('j')) =>
+                    self.rule = (self.rule + 1).min(self.rules.lines().len() - 1),
+                (Focus::Rules, KeyCode::Left | KeyCode::Char('h' | '-')) => self.change_rules(|rules, i| rules.adjust(i, false)),
+                (Focus::Rules, KeyCode::Right | KeyCode::Char('l' | '+') | KeyCode::Enter) =>
+                    self.change_rules(|rules, i| rules.adjust(i, true)),
+
+                (Focus::Rankings | Focus::Rules, KeyCode::Char('d')) => self.change_rules(|rules, _| rules.adjust(0, true)),
+                (Focus::Rankings | Focus::Rules, KeyCode::Char('u')) => {
+                    let initial = self.initial.clone();
+                    self.change_rules(|rules, _| *rules = initial);
+                },
+                _ => {}
+            }
+        }
+    }
+
+    /// The current method's entries, in the order they're shown.
+    fn sorted_entries(&self) -> Vec<&RankEntry> {
+        let mut entries: Vec<&RankEntry> = self.results.methods.get(self.method)
+            .map(|m| m.entries.iter().collect())
+            .unwrap_or_default();
+        entries.sort_by(|a, b| self.sort.compare(a, b).then(a.rank.cmp(&b.rank)));
+        if self.reverse {
+            entries.reverse();
+        }
+        entries
+    }
+
+    fn selected_entry(&self) -> Option<&RankEntry> {
+        self.table.selected().and_then(|i| self.sorted_entries().get(i).copied())
+    }
+
+    /// Makes a change that reorders the table, keeping the same player selected.
+    fn resort(&mut self, change: impl FnOnce(&mut Self)) {
+        let player = self.selected_entry().map(|e| e.player.clone());
+        change(self);
+        let index = player.and_then(|p| self.sorted_entries().iter().position(|e| e.player == p));
+        self.table.select(Some(index.unwrap_or(0)));
+    }
+
+    fn switch_method(&mut self, index: usize) {
+        self.resort(|app| app.method = index % METHODS.len());
+    }
+
+    /// Changes the rules and rescores, if they changed.
+    fn change_rules(&mut self, change: impl FnOnce(&mut Rules, usize)) {
+        let before = self.rules.clone();
+        change(&mut self.rules, self.rule);
+        if self.rules == before {
+            return;
+        }
+
+        let mut pr = self.base.clone();
+        pr.scoring_mut().drop_worst = self.rules.drop_worst;
+        pr.scoring_mut().points = self.rules.points.clone();
+        pr.qualification_mut().minimum_events = self.rules.minimum_events;
+        let results = (self.score)(pr);
+        self.resort(|app| app.results = results);
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [tabs, main, help] = Layout::vertical([Constraint::Length(3), Constraint::Min(5), Constraint::Length(1)])
+            .areas(frame.area());
+        let [rankings, rules] = Layout::horizontal([Constraint::Min(40), Constraint::Length(28)]).areas(main);
+
+        let title = match (&self.results.season, &self.results.game) {
+            (Some(season), Some(game)) => format!(" {} {} ", season, game),
+            (Some(name), None) | (None, Some(name)) => format!(" {} ", name),
+            (None, None) => " Power Rankings ".to_string()
+        };
+        frame.render_widget(Tabs::new(METHODS.iter().map(|m| m.short))
+            .select(self.method)
+            .highlight_style(Style::new().bold().reversed())
+            .block(Block::bordered().title(title)), tabs);
+
+        self.draw_rankings(frame, rankings);
+        self.draw_rules(frame, rules);
+
+        let keys = match self.focus {
+            Focus::Rankings => "←/→ method  ↑/↓ player  Enter events  s sort  r reverse  Tab rules  d drop-worst  u undo  q quit",
+            Focus::Player => "Esc back",
+            Focus::Rules => "↑/↓ rule  ←/→ change  d drop-worst  u undo  Tab back"
+        };
+        frame.render_widget(Paragraph::new(keys).dim(), help);
+
+        if self.focus == Focus::Player {
+            let area = main.inner(ratatui::layout::Margin::new(2, 1));
+            frame.render_widget(Clear, area);
+            self.draw_player(frame, area);
+        }
+    }
+
+    fn draw_rankings(&mut self, frame: &mut Frame, area: Rect) {
+        let header = Row::new(SortColumn::ALL.iter().map(|c| {
+            let arrow = if *c != self.sort { "" } else if self.reverse { " ▼" } else { " ▲" };
+            format!("{}{}", c.title(), arrow)
+        })).bold();
+        let rows: Vec<Row> = self.sorted_entries().into_iter().map(|e| Row::new(vec![
+            e.rank.to_string(),
+            e.player.clone(),
+            format!("{:.4}", e.score),
+            e.events_entered.to_string(),
+            e.seeded_events.to_string(),
+            e.seed_performance.map(|s| format!("{:+.2}", s)).unwrap_or_else(|| "-".to_string())
+        ])).collect();
+        let title = METHODS.get(self.method).map(|m| format!(" {} ", m.title)).unwrap_or_default();
+        let table = Table::new(rows, [Constraint::Length(7), Constraint::Min(16), Constraint::Length(10),
+                Constraint::Length(9), Constraint::Length(9), Constraint::Length(7)])
+            .header(header)
+            .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .block(Block::bordered().title(title));
+        frame.render_stateful_widget(table, area, &mut self.table);
+    }
+
+    fn draw_rules(&self, frame: &mut Frame, area: Rect) {
+        let lines: Vec<Line> = self.rules.lines().into_iter().enumerate().map(|(i, text)| {
+            if self.focus == Focus::Rules && i == self.rule { Line::from(text).reversed() } else { Line::from(text) }
+        }).collect();
+        let title = if self.rules == self.initial { " Rules " } else { " Rules (changed) " };
+        frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(title)), area);
+    }
+
+    fn draw_player(&self, frame: &mut Frame, area: Rect) {
+        let Some(entry) = self.selected_entry() else { return };
+        let header = Row::new(["Event", "Date", "Entrants", "Place", "Bracket", "Seed", "Points"]).bold();
+        let rows: Vec<Row> = entry.events.iter().map(|event| {
+            let summary = self.results.events.get(event.event_id as usize);
+            let dropped = if entry.dropped_event == Some(event.event_id) { "*" } else { "" };
+            Row::new(vec![
+                event.event.clone(),
+                summary.and_then(|s| s.date).map(|d| d.to_string()).unwrap_or_else(|| "?".to_string()),
+                summary.map(|s| s.entrants.to_string()).unwrap_or_else(|| "?".to_string()),
+                event.placement.to_string(),
+                event.raw_placement.unwrap_or(event.placement).to_string(),
+                event.seed.map(|s| s.to_string()).unwrap_or_else(|| "-".to_string()),
+                event.points.map(|p| format!("{:.2}{}", p, dropped)).unwrap_or_else(|| format!("-{}", dropped))
+            ])
+        }).collect();
+        let title = format!(" {}: #{} in {} (* dropped) ", entry.player, entry.rank,
+            METHODS.get(self.method).map(|m| m.short).unwrap_or(""));
+        let table = Table::new(rows, [Constraint::Min(20), Constraint::Length(10), Constraint::Length(8),
+                Constraint::Length(5), Constraint::Length(7), Constraint::Length(5), Constraint::Length(9)])
+            .header(header)
+            .block(Block::bordered().title(title));
+        frame.render_widget(table, area);
+    }
+}
+
+fn ordinal(n: u32) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th"
+    };
+    format!("{}{}", n, suffix)
+}